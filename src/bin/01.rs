@@ -1,63 +1,79 @@
-use pest::Parser;
-use pest_derive::Parser;
-
-use rayon::iter::ParallelBridge;
-use rayon::iter::ParallelIterator;
+use advent_of_code::matcher::Matcher;
 
 advent_of_code::solution!(1);
 
+/// Spelled out digits of part two, in English.
+const ENGLISH: [(&str, u32); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+/// Finds the digits of a line, written with digit characters or with the words of a vocabulary.
+/// Words may overlap: "eightwo" is an 8 then a 2.
+struct DigitMatcher {
+    matcher: Matcher<u32>,
+}
+
+impl DigitMatcher {
+    /// Digit characters and the `(word, digit)` pairs of `vocabulary`.
+    fn new<'a>(vocabulary: impl IntoIterator<Item = &'a (&'a str, u32)>) -> Self {
+        let digits = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+        let digits = digits.into_iter().zip(0..);
+        Self {
+            matcher: Matcher::new(digits.chain(vocabulary.into_iter().copied())),
+        }
+    }
+
+    /// The first and last digits of `line`, the same one if there is only one.
+    /// When several words start at the same place, the longest one counts.
+    fn first_and_last(&self, line: &str) -> Option<(u32, u32)> {
+        let mut matches = self.matcher.find_iter(line.as_bytes());
+        let first_match = matches.next()?;
+        let (mut first, mut last) = (first_match, first_match);
+        for m in matches {
+            if m.start < first.start || (m.start == first.start && m.end > first.end) {
+                first = m;
+            }
+            if m.start > last.start || (m.start == last.start && m.end > last.end) {
+                last = m;
+            }
+        }
+        Some((*first.value, *last.value))
+    }
+
+    /// The sum of the two digit numbers made of the first and last digits of every line,
+    /// [`None`] if a line has no digit.
+    fn calibration(&self, input: &str) -> Option<u32> {
+        input
+            .lines()
+            .map(|line| {
+                self.first_and_last(line)
+                    .map(|(first, last)| 10 * first + last)
+            })
+            .sum()
+    }
+}
+
 pub fn part_one(input: &str) -> Option<u32> {
     input
         .lines()
         .map(|l| l.chars().filter_map(|c| c.to_digit(10)))
         .map(|mut digits| {
             let first = digits.next().unwrap();
-            10 * first + digits.last().unwrap_or(first)
+            10 * first + digits.next_back().unwrap_or(first)
         })
         .reduce(|acc, el| acc + el)
 }
 
-#[derive(Parser)]
-#[grammar = "src/bin/01.pest"]
-pub struct NumberParser;
-
-fn parse_string(input: &str) -> Vec<u32> {
-    let Ok(mut parse) = NumberParser::parse(Rule::numbers, input) else {
-        panic!();
-    };
-    parse
-        .next()
-        .unwrap()
-        .into_inner()
-        .filter_map(
-            |record| match record.into_inner().next().unwrap().as_rule() {
-                Rule::one => Some(1),
-                Rule::two => Some(2),
-                Rule::three => Some(3),
-                Rule::four => Some(4),
-                Rule::five => Some(5),
-                Rule::six => Some(6),
-                Rule::seven => Some(7),
-                Rule::eight => Some(8),
-                Rule::nine => Some(9),
-                _ => unreachable!(),
-            },
-        )
-        .collect()
-}
-
 pub fn part_two(input: &str) -> Option<u32> {
-    Some(
-        input
-            .lines()
-            .par_bridge()
-            .map(parse_string)
-            .map(|digits| {
-                let first = digits.first().unwrap();
-                10 * first + digits.last().unwrap_or(first)
-            })
-            .reduce(|| 0, |acc, el| acc + el),
-    )
+    DigitMatcher::new(&ENGLISH).calibration(input)
 }
 
 #[cfg(test)]
@@ -79,4 +95,46 @@ mod tests {
         ));
         assert_eq!(result, Some(281));
     }
+
+    #[test]
+    fn test_overlapping_words() {
+        let digits = DigitMatcher::new(&ENGLISH);
+        assert_eq!(digits.first_and_last("eightwo"), Some((8, 2)));
+        assert_eq!(digits.first_and_last("oneight"), Some((1, 8)));
+        assert_eq!(digits.first_and_last("xxtwonexx"), Some((2, 1)));
+        assert_eq!(digits.first_and_last("sevenine"), Some((7, 9)));
+        assert_eq!(digits.first_and_last("nothing"), None);
+        assert_eq!(part_two("one\nabc\n"), None);
+    }
+
+    #[test]
+    fn test_other_vocabularies() {
+        let mut with_zero = ENGLISH.to_vec();
+        with_zero.push(("zero", 0));
+        let digits = DigitMatcher::new(&with_zero);
+        assert_eq!(digits.first_and_last("zerone"), Some((0, 1)));
+        assert_eq!(digits.first_and_last("5zero"), Some((5, 0)));
+
+        let french = [
+            ("zéro", 0),
+            ("un", 1),
+            ("deux", 2),
+            ("trois", 3),
+            ("quatre", 4),
+            ("cinq", 5),
+            ("six", 6),
+            ("sept", 7),
+            ("huit", 8),
+            ("neuf", 9),
+        ];
+        let digits = DigitMatcher::new(&french);
+        assert_eq!(digits.first_and_last("zéroneuf"), Some((0, 9)));
+        assert_eq!(digits.first_and_last("quatreize8"), Some((4, 8)));
+        assert_eq!(digits.first_and_last("huitrois"), Some((8, 3)));
+
+        // the longest of the words starting at the same place.
+        let digits = DigitMatcher::new(&[("eigh", 1), ("eight", 8)]);
+        assert_eq!(digits.first_and_last("eight"), Some((8, 8)));
+        assert_eq!(digits.first_and_last("eigh2eighx"), Some((1, 1)));
+    }
 }
@@ -1,109 +1,430 @@
-advent_of_code::solution!(2);
-struct Handful {
-    red: u32,
-    green: u32,
-    blue: u32,
-}
-
-impl From<&str> for Handful {
-    fn from(value: &str) -> Self {
-        let mut red = 0;
-        let mut green = 0;
-        let mut blue = 0;
-
-        let cubes_groups = value.split(',');
-
-        for cube_group in cubes_groups {
-            let cube_group = cube_group.strip_prefix(' ').unwrap_or(cube_group);
-            let cube_group = cube_group.strip_suffix(' ').unwrap_or(cube_group);
-            let (amount, color) = cube_group.split_once(' ').unwrap();
-            let amount = u32::from_str_radix(amount, 10).unwrap();
-            match color {
-                "red" => red = amount,
-                "green" => green = amount,
-                "blue" => blue = amount,
-                _ => panic!(),
+use std::fmt::{self, Display};
+
+use advent_of_code::parse::{OrReport, ParseError, Source};
+use advent_of_code::template::Options;
+
+advent_of_code::solution!(2, extras);
+
+/// The bag of part one. Other bags can be tried with `--bag "<n> <color>, ..."`.
+const PUZZLE_BAG: &str = "12 red, 13 green, 14 blue";
+
+/// The colors multiplied together for the power of a game in part two.
+const POWER_COLORS: [&str; 3] = ["red", "green", "blue"];
+
+/// Cube counts by color, in the order the colors first appear. Used for handfuls and bags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Cubes<'a> {
+    counts: Vec<(&'a str, u32)>,
+}
+
+type Handful<'a> = Cubes<'a>;
+type Bag<'a> = Cubes<'a>;
+
+impl<'a> Cubes<'a> {
+    /// Parses `3 blue, 4 red`, each color at most once.
+    fn parse(source: &Source<'a>, fragment: &'a str) -> Result<Self, ParseError> {
+        let mut cubes = Cubes::default();
+        for group in fragment.split(',') {
+            let group = group.trim();
+            let (amount, color) = source.split_once(group, " ")?;
+            let color = color.trim();
+            if cubes.get(color).is_some() {
+                return Err(source.error(color, format!("{color} cubes counted twice")));
             }
+            cubes.counts.push((color, source.number(amount)?));
         }
+        Ok(cubes)
+    }
+
+    fn get(&self, color: &str) -> Option<u32> {
+        self.counts
+            .iter()
+            .find(|&&(c, _)| c == color)
+            .map(|&(_, count)| count)
+    }
 
-        Handful { red, green, blue }
+    /// Orders the colors like in `other`, the ones it doesn't have last.
+    fn sort_like(&mut self, other: &Cubes) {
+        self.counts.sort_by_key(|&(color, _)| {
+            other
+                .counts
+                .iter()
+                .position(|&(c, _)| c == color)
+                .unwrap_or(usize::MAX)
+        });
+    }
+
+    /// Raises the count of `color` to `count` if it is lower.
+    fn raise(&mut self, color: &'a str, count: u32) {
+        match self.counts.iter_mut().find(|(c, _)| *c == color) {
+            Some((_, current)) => *current = (*current).max(count),
+            None => self.counts.push((color, count)),
+        }
     }
 }
-struct Game {
+
+impl Display for Cubes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (color, count)) in self.counts.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{count} {color}")?;
+        }
+        Ok(())
+    }
+}
+
+struct Game<'a> {
     id: u32,
-    handfuls_drawn: Vec<Handful>,
+    handfuls_drawn: Vec<Handful<'a>>,
 }
 
-impl From<&str> for Game {
-    fn from(value: &str) -> Self {
-        let (game, handfuls) = value.split_once(':').unwrap();
-        let game_id = u32::from_str_radix(game.split_once(' ').unwrap().1, 10).unwrap();
-        let mut handfuls_drawn = Vec::new();
-        let handfuls = handfuls.split(';');
-        for handful in handfuls {
-            handfuls_drawn.push(Handful::from(handful))
-        }
-        Game {
-            id: game_id,
-            handfuls_drawn,
-        }
+/// A handful with more cubes of a color than the bag holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Violation<'a> {
+    /// Position of the handful in the game, from 1.
+    handful: usize,
+    color: &'a str,
+    drawn: u32,
+    available: u32,
+}
+
+impl Display for Violation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "handful {} draws {} {} but the bag holds {}",
+            self.handful, self.drawn, self.color, self.available
+        )
     }
 }
 
-impl Game {
-    fn power(&self) -> u32 {
-        let mut min_red = 0;
-        let mut min_green = 0;
-        let mut min_blue = 0;
+impl<'a> Game<'a> {
+    fn parse(source: &Source<'a>, line: &'a str) -> Result<Self, ParseError> {
+        let (game, handfuls) = source.key_value(line)?;
+        let id = game
+            .strip_prefix("Game ")
+            .ok_or_else(|| source.error(game, "expected \"Game <id>\""))?;
+        let id = source.number(id)?;
+        let handfuls_drawn = handfuls
+            .split(';')
+            .map(|handful| Cubes::parse(source, handful))
+            .collect::<Result<_, _>>()?;
+        Ok(Game { id, handfuls_drawn })
+    }
+
+    /// Every handful and color drawing more cubes than `bag` holds, colors missing from the bag
+    /// holding none.
+    fn violations<'b>(&'b self, bag: &'b Bag) -> impl Iterator<Item = Violation<'a>> + 'b {
+        self.handfuls_drawn
+            .iter()
+            .enumerate()
+            .flat_map(move |(i, handful)| {
+                handful.counts.iter().filter_map(move |&(color, drawn)| {
+                    let available = bag.get(color).unwrap_or(0);
+                    (drawn > available).then_some(Violation {
+                        handful: i + 1,
+                        color,
+                        drawn,
+                        available,
+                    })
+                })
+            })
+    }
+
+    fn is_possible(&self, bag: &Bag) -> bool {
+        self.violations(bag).next().is_none()
+    }
 
+    /// The fewest cubes of each color the bag must hold for the game to be possible.
+    fn minimal_bag(&self) -> Bag<'a> {
+        let mut bag = Bag::default();
         for handful in &self.handfuls_drawn {
-            min_red = min_red.max(handful.red);
-            min_green = min_green.max(handful.green);
-            min_blue = min_blue.max(handful.blue);
+            for &(color, count) in &handful.counts {
+                bag.raise(color, count);
+            }
         }
+        bag
+    }
 
-        min_red * min_green * min_blue
+    /// The product of the minimal counts of `colors`, zero if one is never drawn.
+    fn power(&self, colors: &[&str]) -> u32 {
+        let bag = self.minimal_bag();
+        colors
+            .iter()
+            .map(|color| bag.get(color).unwrap_or(0))
+            .product()
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let valid_game = |game: &Game| {
-        for handful in &game.handfuls_drawn {
-            if handful.red > 12 {
-                return false;
+fn parse_games(input: &str) -> Result<Vec<Game<'_>>, ParseError> {
+    let source = Source::new(input);
+    source
+        .lines()
+        .map(|line| Game::parse(&source, line))
+        .collect()
+}
+
+/// Sum of the ids of the games possible with `bag`.
+fn possible_games(games: &[Game], bag: &Bag) -> u32 {
+    games
+        .iter()
+        .filter(|game| game.is_possible(bag))
+        .map(|game| game.id)
+        .sum()
+}
+
+/// Why each game is possible or not with `bag`, along with its minimal bag.
+fn report(games: &[Game], bag: &Bag) -> String {
+    let mut report = format!("Bag: {bag}\n");
+    for game in games {
+        let violations: Vec<Violation> = game.violations(bag).collect();
+        if violations.is_empty() {
+            report += &format!("Game {}: possible", game.id);
+        } else {
+            report += &format!("Game {}: impossible", game.id);
+        }
+        let mut minimal_bag = game.minimal_bag();
+        minimal_bag.sort_like(bag);
+        report += &format!(", minimal bag {minimal_bag}\n");
+        for violation in violations {
+            report += &format!("  {violation}\n");
+        }
+    }
+    report
+}
+
+/// Largest count of a color considered when estimating the bag, unless passed with
+/// `--estimate-limit <n>`.
+const ESTIMATE_LIMIT: usize = 200;
+
+/// Twice the drop in log-likelihood bounding the 95% ranges: the 0.95 quantile of the
+/// chi-squared distribution with one degree of freedom.
+const CHI_SQUARED_95: f64 = 3.841;
+
+/// Observed handfuls, summarized for the likelihood of a bag.
+///
+/// Cubes of a handful are drawn without replacement, so for a bag of `N_c` cubes of each color
+/// and `T` cubes in total, a handful of `k_c` cubes of each color and `n` in total has the
+/// multivariate hypergeometric probability `∏ C(N_c, k_c) / C(T, n)`. The cubes go back in the
+/// bag between handfuls, which are independent.
+struct Observations<'a> {
+    colors: Vec<&'a str>,
+    /// `drawn[c][k]`, handfuls with `k` cubes of the color `c`.
+    drawn: Vec<Vec<u32>>,
+    /// `sizes[n]`, handfuls of `n` cubes.
+    sizes: Vec<u32>,
+    /// `ln(k!)` for every `k` up to the largest bag considered.
+    ln_factorials: Vec<f64>,
+}
+
+impl<'a> Observations<'a> {
+    fn new(games: &[Game<'a>], limit: usize) -> Self {
+        let mut colors = Vec::new();
+        for handful in games.iter().flat_map(|game| &game.handfuls_drawn) {
+            for &(color, _) in &handful.counts {
+                if !colors.contains(&color) {
+                    colors.push(color);
+                }
             }
-            if handful.green > 13 {
-                return false;
+        }
+        let mut drawn = vec![Vec::new(); colors.len()];
+        let mut sizes = Vec::new();
+        let count = |histogram: &mut Vec<u32>, k: usize| {
+            if histogram.len() <= k {
+                histogram.resize(k + 1, 0);
             }
-            if handful.blue > 14 {
-                return false;
+            histogram[k] += 1;
+        };
+        for handful in games.iter().flat_map(|game| &game.handfuls_drawn) {
+            for (c, color) in colors.iter().enumerate() {
+                count(&mut drawn[c], handful.get(color).unwrap_or(0) as usize);
             }
+            count(
+                &mut sizes,
+                handful.counts.iter().map(|&(_, k)| k as usize).sum(),
+            );
         }
-        return true;
-    };
-    input
-        .lines()
-        .map(Game::from)
-        .filter_map(|game| {
-            if valid_game(&game) {
-                Some(game.id)
-            } else {
-                None
+
+        let largest = limit.max(sizes.len()) * colors.len().max(1);
+        let mut ln_factorials = vec![0.0; largest + 1];
+        for k in 1..=largest {
+            ln_factorials[k] = ln_factorials[k - 1] + (k as f64).ln();
+        }
+        Self {
+            colors,
+            drawn,
+            sizes,
+            ln_factorials,
+        }
+    }
+
+    /// The fewest cubes of each color making every handful possible.
+    fn minimal_bag(&self) -> Vec<usize> {
+        self.drawn
+            .iter()
+            .map(|histogram| histogram.len() - 1)
+            .collect()
+    }
+
+    fn ln_binomial(&self, n: usize, k: usize) -> f64 {
+        self.ln_factorials[n] - self.ln_factorials[k] - self.ln_factorials[n - k]
+    }
+
+    /// Log-likelihood of the observations, for a bag at least as large as the minimal one.
+    fn log_likelihood(&self, bag: &[usize]) -> f64 {
+        let histogram_sum = |histogram: &[u32], n: usize| -> f64 {
+            histogram
+                .iter()
+                .enumerate()
+                .filter(|&(_, &handfuls)| handfuls > 0)
+                .map(|(k, &handfuls)| f64::from(handfuls) * self.ln_binomial(n, k))
+                .sum()
+        };
+        let total = bag.iter().sum();
+        bag.iter()
+            .zip(&self.drawn)
+            .map(|(&n, histogram)| histogram_sum(histogram, n))
+            .sum::<f64>()
+            - histogram_sum(&self.sizes, total)
+    }
+
+    /// Raises the likelihood one color at a time, each count between the minimal bag and
+    /// `limit`, until no single change helps. The count of `fixed` is left alone.
+    /// Returns the log-likelihood reached.
+    fn maximize(&self, bag: &mut [usize], fixed: Option<usize>, limit: usize) -> f64 {
+        let lowest = self.minimal_bag();
+        let mut best = self.log_likelihood(bag);
+        loop {
+            let mut improved = false;
+            for c in (0..bag.len()).filter(|&c| Some(c) != fixed) {
+                for n in lowest[c]..=limit.max(lowest[c]) {
+                    let current = bag[c];
+                    bag[c] = n;
+                    let likelihood = self.log_likelihood(bag);
+                    if likelihood > best + 1e-9 {
+                        best = likelihood;
+                        improved = true;
+                    } else {
+                        bag[c] = current;
+                    }
+                }
+            }
+            if !improved {
+                return best;
+            }
+        }
+    }
+}
+
+/// The most likely count of a color, with the counts within its 95% profile likelihood range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Estimate<'a> {
+    color: &'a str,
+    most_likely: usize,
+    lowest: usize,
+    /// [`None`] when the range reaches the limit, the data can't tell how large it is.
+    highest: Option<usize>,
+}
+
+impl Display for Estimate<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let range = match self.highest {
+            Some(highest) => format!("{}..={highest}", self.lowest),
+            None => format!("{}..", self.lowest),
+        };
+        write!(f, "{:>6} {:>5}  {range}", self.color, self.most_likely)
+    }
+}
+
+/// Maximum likelihood estimate of the bag all the games were played with, counts up to `limit`.
+fn estimate_bag<'a>(games: &[Game<'a>], limit: usize) -> Vec<Estimate<'a>> {
+    let observations = Observations::new(games, limit);
+    let mut most_likely = observations.minimal_bag();
+    let best = observations.maximize(&mut most_likely, None, limit);
+
+    (0..observations.colors.len())
+        .map(|c| {
+            // the profile likelihood: the best bag with the count of `c` set. It is taken to
+            // only decrease away from the most likely count.
+            let within_range = |n: usize| {
+                let mut bag = most_likely.clone();
+                bag[c] = n;
+                let likelihood = observations.maximize(&mut bag, Some(c), limit);
+                2.0 * (best - likelihood) <= CHI_SQUARED_95
+            };
+            let lowest = observations.minimal_bag()[c];
+            let low = (lowest..most_likely[c])
+                .rev()
+                .take_while(|&n| within_range(n))
+                .last()
+                .unwrap_or(most_likely[c]);
+            let high = (most_likely[c] + 1..=limit)
+                .take_while(|&n| within_range(n))
+                .last()
+                .unwrap_or(most_likely[c]);
+            Estimate {
+                color: observations.colors[c],
+                most_likely: most_likely[c],
+                lowest: low,
+                highest: (high < limit).then_some(high),
             }
         })
-        .reduce(|acc, el| acc + el)
+        .collect()
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    let games = parse_games(input).or_report()?;
+    let bag = Cubes::parse(&Source::new(PUZZLE_BAG), PUZZLE_BAG).or_report()?;
+    Some(possible_games(&games, &bag))
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    input
-        .lines()
-        .map(Game::from)
-        .map(|game| game.power())
-        .reduce(|acc, el| acc + el)
+    let games = parse_games(input).or_report()?;
+    Some(games.iter().map(|game| game.power(&POWER_COLORS)).sum())
+}
+
+/// Counts the games possible with the bag passed with `--bag`, explains why each game is
+/// possible or not with `--report`, and estimates the bag of the games with `--estimate`.
+fn extras(input: &str, options: &Options) {
+    // parse errors were already reported by the parts.
+    let Ok(games) = parse_games(input) else {
+        return;
+    };
+    let reported = options.flag("--report");
+    if reported || options.value("--bag").is_some() {
+        let bag = options.value("--bag").unwrap_or(PUZZLE_BAG);
+        if let Some(bag) = Cubes::parse(&Source::new(bag), bag).or_report() {
+            if reported {
+                print!("{}", report(&games, &bag));
+            }
+            println!(
+                "Games possible with {bag}: {}",
+                possible_games(&games, &bag)
+            );
+        }
+    }
+    if options.flag("--estimate") {
+        let limit = match options.value("--estimate-limit").map(str::parse) {
+            None => ESTIMATE_LIMIT,
+            Some(Ok(limit)) => limit,
+            Some(Err(e)) => {
+                eprintln!("Invalid --estimate-limit: {e}");
+                return;
+            }
+        };
+        println!("Most likely bag, with 95% ranges ({limit} cubes of a color at most):");
+        for estimate in estimate_bag(&games, limit) {
+            println!("{estimate}");
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use advent_of_code::random::Rng;
+
     use super::*;
 
     #[test]
@@ -117,4 +438,121 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(2286));
     }
+
+    #[test]
+    fn test_violations() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let games = parse_games(&input).unwrap();
+        let source = Source::new(PUZZLE_BAG);
+        let bag = Cubes::parse(&source, PUZZLE_BAG).unwrap();
+
+        let violations: Vec<Violation> = games[3].violations(&bag).collect();
+        assert_eq!(
+            violations,
+            [
+                Violation {
+                    handful: 3,
+                    color: "blue",
+                    drawn: 15,
+                    available: 14,
+                },
+                Violation {
+                    handful: 3,
+                    color: "red",
+                    drawn: 14,
+                    available: 12,
+                }
+            ]
+        );
+        assert_eq!(games[2].violations(&bag).count(), 1);
+        assert_eq!(games[0].minimal_bag().to_string(), "6 blue, 4 red, 2 green");
+
+        let report = report(&games, &bag);
+        assert!(report.contains("Game 1: possible, minimal bag 4 red, 2 green, 6 blue\n"));
+        assert!(report.contains("Game 3: impossible, minimal bag 20 red, 13 green, 6 blue\n"));
+        assert!(report.contains("  handful 1 draws 20 red but the bag holds 12\n"));
+    }
+
+    #[test]
+    fn test_other_colors_and_bags() {
+        let input = "Game 7: 2 yellow, 1 red; 3 yellow\nGame 8: 1 purple\n";
+        let games = parse_games(input).unwrap();
+        let bag = "3 yellow, 1 red";
+        let bag = Cubes::parse(&Source::new(bag), bag).unwrap();
+        assert_eq!(possible_games(&games, &bag), 7);
+        assert_eq!(
+            games[1].violations(&bag).next().map(|v| v.to_string()),
+            Some("handful 1 draws 1 purple but the bag holds 0".to_string())
+        );
+        assert_eq!(games[0].power(&["yellow", "red"]), 3);
+        assert_eq!(games[0].power(&POWER_COLORS), 0);
+    }
+
+    /// Games of handfuls drawn from `bag` without replacement, put back after each handful.
+    fn simulate(bag: &[(&str, usize)], handfuls: usize, seed: u64) -> String {
+        let mut rng = Rng::new(seed);
+        let mut cubes: Vec<&str> = bag
+            .iter()
+            .flat_map(|&(color, n)| std::iter::repeat_n(color, n))
+            .collect();
+        let mut input = String::new();
+        for game in 0..handfuls / 5 {
+            let handfuls: Vec<String> = (0..5)
+                .map(|_| {
+                    rng.shuffle(&mut cubes);
+                    let drawn = &cubes[..rng.range_usize(1, cubes.len() - 1)];
+                    bag.iter()
+                        .map(|&(color, _)| (color, drawn.iter().filter(|&&c| c == color).count()))
+                        .filter(|&(_, count)| count > 0)
+                        .map(|(color, count)| format!("{count} {color}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect();
+            input += &format!("Game {}: {}\n", game + 1, handfuls.join("; "));
+        }
+        input
+    }
+
+    #[test]
+    fn test_estimates_the_bag() {
+        let bag = [("red", 12), ("green", 13), ("blue", 14)];
+        let input = simulate(&bag, 500, 2);
+        let games = parse_games(&input).unwrap();
+        let estimates = estimate_bag(&games, ESTIMATE_LIMIT);
+        assert_eq!(estimates.len(), 3);
+        for (color, count) in bag {
+            let estimate = estimates.iter().find(|e| e.color == color).unwrap();
+            let highest = estimate.highest.unwrap();
+            assert!(estimate.lowest <= count && count <= highest, "{estimate}");
+            assert!(estimate.lowest <= estimate.most_likely && estimate.most_likely <= highest);
+            assert!(highest - estimate.lowest <= 10, "{estimate}");
+        }
+    }
+
+    #[test]
+    fn test_unbounded_estimates() {
+        // a single color: every bag of at least 3 cubes draws 3 red for sure.
+        let games = parse_games("Game 1: 3 red; 2 red\n").unwrap();
+        let estimates = estimate_bag(&games, 50);
+        assert_eq!(
+            estimates,
+            [Estimate {
+                color: "red",
+                most_likely: 3,
+                lowest: 3,
+                highest: None,
+            }]
+        );
+        assert_eq!(estimates[0].to_string(), "   red     3  3..");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = parse_games("Game 1: 3 blue, 2 blue").err().unwrap();
+        assert_eq!((error.line, error.column), (1, 19));
+        assert!(parse_games("Game x: 3 blue").is_err());
+        assert!(parse_games("Round 1: 3 blue").is_err());
+        assert!(parse_games("Game 1: blue").is_err());
+    }
 }
@@ -1,214 +1,411 @@
-use std::collections::HashMap;
+use std::io::{self, BufRead};
+use std::iter;
+use std::ops::Range;
 
-advent_of_code::solution!(3);
+use advent_of_code::grid::Grid;
+use advent_of_code::record::{Cell, Frame};
+use advent_of_code::render::Rgb;
+use advent_of_code::search;
+use advent_of_code::template::Options;
 
-fn char_is_number(c: char) -> bool {
-    c as u32 <= '9' as u32 && c as u32 >= '0' as u32
+advent_of_code::solution!(3, extras);
+
+fn is_symbol(byte: u8) -> bool {
+    !byte.is_ascii_digit() && byte != b'.'
 }
-fn is_symbol(c: char) -> bool {
-    !char_is_number(c) && c != '.'
+
+/// The numbers of a row, with their columns.
+fn numbers_in(row: &[u8]) -> impl Iterator<Item = (Range<usize>, u32)> + '_ {
+    let mut x = 0;
+    iter::from_fn(move || {
+        let start = x + row.get(x..)?.iter().position(u8::is_ascii_digit)?;
+        let end = row[start..]
+            .iter()
+            .position(|b| !b.is_ascii_digit())
+            .map_or(row.len(), |len| start + len);
+        x = end;
+        Some((start..end, parse_digits(&row[start..end])))
+    })
 }
-struct NumberParser<'a> {
-    parser_pos: (usize, usize),
-    engine: &'a Engine,
+
+fn parse_digits(digits: &[u8]) -> u32 {
+    digits
+        .iter()
+        .fold(0, |value, digit| 10 * value + u32::from(digit - b'0'))
 }
 
-impl<'a> NumberParser<'a> {
-    fn new(engine: &'a Engine) -> Self {
-        NumberParser {
-            parser_pos: (0, 0),
-            engine: engine,
-        }
-    }
-    fn cur_x_y(&self) -> (usize, usize) {
-        self.parser_pos
-    }
-    fn cur_x(&self) -> usize {
-        self.parser_pos.0
-    }
-    fn cur_x_mut(&mut self) -> &mut usize {
-        &mut self.parser_pos.0
+/// The number of `row` covering column `x`, if any.
+fn number_at(row: &[u8], x: usize) -> Option<u32> {
+    if !row.get(x)?.is_ascii_digit() {
+        return None;
     }
-    fn cur_y(&self) -> usize {
-        self.parser_pos.1
-    }
-    fn cur_y_mut(&mut self) -> &mut usize {
-        &mut self.parser_pos.1
+    let start = row[..x]
+        .iter()
+        .rposition(|b| !b.is_ascii_digit())
+        .map_or(0, |before| before + 1);
+    let end = row[x..]
+        .iter()
+        .position(|b| !b.is_ascii_digit())
+        .map_or(row.len(), |len| x + len);
+    Some(parse_digits(&row[start..end]))
+}
+
+/// Both answers, found in a single pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Totals {
+    part_numbers: u32,
+    gear_ratios: u32,
+}
+
+/// Reads a schematic row by row, with only the rows above and below the current one in memory,
+/// and adds up the part numbers and the gear ratios of the current row.
+struct Scanner<R> {
+    reader: R,
+    /// The rows above, current and below.
+    rows: [Vec<u8>; 3],
+    totals: Totals,
+}
+
+impl<R: BufRead> Scanner<R> {
+    fn new(reader: R) -> Self {
+        Scanner {
+            reader,
+            rows: Default::default(),
+            totals: Totals::default(),
+        }
     }
-    fn done_parsing(&self) -> bool {
-        self.cur_y() >= self.engine.max_y
+
+    /// Reads the next row into the row below, empty at the end of the input.
+    fn read_row(&mut self) -> io::Result<bool> {
+        let row = &mut self.rows[2];
+        row.clear();
+        if self.reader.read_until(b'\n', row)? == 0 {
+            return Ok(false);
+        }
+        while let Some(b'\n' | b'\r') = row.last() {
+            row.pop();
+        }
+        Ok(true)
     }
-    fn increment_pos(&mut self) {
-        *self.cur_x_mut() += 1;
-        if self.cur_x() >= self.engine.max_x {
-            *self.cur_x_mut() = 0;
-            *self.cur_y_mut() += 1;
+
+    fn scan(mut self) -> io::Result<Totals> {
+        self.read_row()?;
+        loop {
+            self.rows.rotate_left(1);
+            let more = self.read_row()?;
+            if self.rows[1].is_empty() && !more {
+                return Ok(self.totals);
+            }
+            self.scan_current_row();
         }
     }
 
-    /// Return `None` if done parsing
-    fn parse_next_number(&mut self) -> Option<ParsedNumber> {
-        while !self.done_parsing() {
-            match self.engine.get_unchecked(self.cur_x_y()) {
-                num_char if char_is_number(num_char) => {
-                    let mut num = num_char.to_string();
-                    let num_x = self.cur_x();
-                    let num_y = self.cur_y();
-                    while let Some(c) = self.engine.get((self.cur_x() + 1, self.cur_y())) {
-                        if char_is_number(c) {
-                            // consume char
-                            self.increment_pos();
-                            num += &c.to_string();
-                        } else {
-                            break;
-                        }
+    fn scan_current_row(&mut self) {
+        let [above, row, below] = &self.rows;
+        for (columns, value) in numbers_in(row) {
+            let around = columns.start.saturating_sub(1)..columns.end + 1;
+            let is_part = [above, row, below].into_iter().any(|row| {
+                let around = around.start.min(row.len())..around.end.min(row.len());
+                row[around].iter().copied().any(is_symbol)
+            });
+            if is_part {
+                self.totals.part_numbers += value;
+            }
+        }
+        for x in (0..row.len()).filter(|&x| row[x] == b'*') {
+            let mut count = 0;
+            let mut ratio = 1;
+            for row in [above, row, below] {
+                // a number right over or under the star covers both diagonals.
+                let touching = match number_at(row, x) {
+                    Some(value) => [Some(value), None],
+                    None => [
+                        x.checked_sub(1).and_then(|x| number_at(row, x)),
+                        number_at(row, x + 1),
+                    ],
+                };
+                for value in touching.into_iter().flatten() {
+                    if count < 2 {
+                        ratio *= value;
                     }
-                    // precedent pos has been consummed for sure
-                    self.increment_pos();
-
-                    return Some(ParsedNumber {
-                        start_x: num_x,
-                        y: num_y,
-                        length: num.len(),
-                        number_str: num,
-                    });
+                    count += 1;
                 }
-                _ => self.increment_pos(),
+            }
+            if count == 2 {
+                self.totals.gear_ratios += ratio;
             }
         }
-        None
     }
 }
 
-impl<'a> Iterator for NumberParser<'a> {
-    type Item = ParsedNumber;
-    fn next(&mut self) -> Option<Self::Item> {
-        self.parse_next_number()
-    }
+/// A number of the schematic, digits on a single line from `start`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Number {
+    value: u32,
+    start: (usize, usize),
+    length: usize,
 }
 
-#[derive(Clone)]
-struct ParsedNumber {
-    start_x: usize,
-    length: usize,
-    y: usize,
-    number_str: String,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Symbol {
+    char: char,
+    pos: (usize, usize),
 }
 
-impl ParsedNumber {
-    fn number(&self) -> u32 {
-        u32::from_str_radix(&self.number_str, 10).unwrap()
-    }
-    fn get_surround(&self) -> Vec<(usize, usize)> {
-        let mut surround = Vec::new();
-        // on left
-        if let Some(x) = self.start_x.checked_sub(1) {
-            surround.push((x, self.y));
-        }
-        // on right
-        surround.push((self.start_x + self.length, self.y));
+/// Numbers and symbols linked together by adjacency, directly or through each other.
+/// Both lists hold indices in the [`Schematic`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Component {
+    numbers: Vec<usize>,
+    symbols: Vec<usize>,
+}
+
+/// Which symbols are gears: the ones next to exactly `neighbours` numbers, and only the
+/// `char` ones if set.
+#[derive(Debug, Clone, Copy)]
+struct GearRule {
+    char: Option<char>,
+    neighbours: usize,
+}
 
-        // on top
-        if let Some(y) = self.y.checked_sub(1) {
-            for x in (self.start_x.saturating_sub(1))..=(self.start_x + self.length) {
-                surround.push((x, y));
+/// The gears of part two.
+const STAR_GEARS: GearRule = GearRule {
+    char: Some('*'),
+    neighbours: 2,
+};
+
+/// The numbers and symbols of an engine schematic, with the ones next to each other, diagonals
+/// included. Numbers and symbols are identified by their index, in reading order.
+struct Schematic {
+    map: Grid<char>,
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    /// Symbols next to each number.
+    symbols_around: Vec<Vec<usize>>,
+    /// Numbers next to each symbol.
+    numbers_around: Vec<Vec<usize>>,
+}
+
+impl From<&str> for Schematic {
+    fn from(value: &str) -> Self {
+        let map = Grid::parse(value, |c| c).unwrap();
+        let mut symbol_at = Grid::new(map.width(), map.height(), None);
+        let mut symbols = Vec::new();
+        for (y, line) in value.lines().enumerate() {
+            for (x, &byte) in line.as_bytes().iter().enumerate() {
+                if is_symbol(byte) {
+                    symbol_at[(x, y)] = Some(symbols.len());
+                    symbols.push(Symbol {
+                        char: byte as char,
+                        pos: (x, y),
+                    });
+                }
             }
         }
 
-        // on bottom
-        for x in (self.start_x.saturating_sub(1))..=(self.start_x + self.length) {
-            surround.push((x, self.y + 1));
+        let mut numbers = Vec::new();
+        let mut symbols_around = Vec::new();
+        let mut numbers_around = vec![Vec::new(); symbols.len()];
+        for (y, line) in value.lines().enumerate() {
+            for (columns, value) in numbers_in(line.as_bytes()) {
+                let mut around = Vec::new();
+                for y in y.saturating_sub(1)..=y + 1 {
+                    for x in columns.start.saturating_sub(1)..=columns.end {
+                        if let Some(&Some(s)) = symbol_at.get((x, y)) {
+                            around.push(s);
+                            numbers_around[s].push(numbers.len());
+                        }
+                    }
+                }
+                symbols_around.push(around);
+                numbers.push(Number {
+                    value,
+                    start: (columns.start, y),
+                    length: columns.len(),
+                });
+            }
+        }
+        Schematic {
+            map,
+            numbers,
+            symbols,
+            symbols_around,
+            numbers_around,
         }
-        surround
     }
 }
 
-struct Engine {
-    map: Vec<Vec<char>>,
-    max_x: usize,
-    max_y: usize,
-}
+impl Schematic {
+    /// Whether the number is a part number, that is next to a symbol.
+    fn is_part(&self, number: usize) -> bool {
+        self.symbols_of(number).next().is_some()
+    }
 
-impl From<&str> for Engine {
-    fn from(value: &str) -> Self {
-        let map: Vec<Vec<char>> = value.lines().map(|line| line.chars().collect()).collect();
-        let max_x = map[0].len();
-        let max_y = map.len();
-        assert!(map.len() > 0);
-        Engine { map, max_x, max_y }
+    fn part_numbers(&self) -> impl Iterator<Item = &Number> {
+        (0..self.numbers.len())
+            .filter(|&n| self.is_part(n))
+            .map(|n| &self.numbers[n])
     }
-}
 
-impl Engine {
-    fn get(&self, pos: (usize, usize)) -> Option<char> {
-        if pos.1 < self.map.len() && pos.0 < self.map[0].len() {
-            Some(self.get_unchecked(pos))
-        } else {
-            None
-        }
+    /// The symbols next to the number.
+    fn symbols_of(&self, number: usize) -> impl Iterator<Item = &Symbol> {
+        self.symbols_around[number]
+            .iter()
+            .map(|&s| &self.symbols[s])
     }
-    fn get_unchecked(&self, pos: (usize, usize)) -> char {
-        self.map[pos.1][pos.0]
+
+    /// The numbers next to the symbol.
+    fn numbers_of(&self, symbol: usize) -> impl Iterator<Item = &Number> {
+        self.numbers_around[symbol]
+            .iter()
+            .map(|&n| &self.numbers[n])
     }
-    fn is_part(&self, parsed_number: &ParsedNumber) -> bool {
-        for pos in parsed_number.get_surround() {
-            if let Some(c) = self.get(pos) {
-                if is_symbol(c) {
-                    return true;
+
+    /// Groups of numbers and symbols linked by adjacency, a number or symbol alone included.
+    fn components(&self) -> Vec<Component> {
+        #[derive(Clone, PartialEq, Eq, Hash)]
+        enum Node {
+            Number(usize),
+            Symbol(usize),
+        }
+        let mut seen_numbers = vec![false; self.numbers.len()];
+        let mut seen_symbols = vec![false; self.symbols.len()];
+        let starts = (0..self.numbers.len())
+            .map(Node::Number)
+            .chain((0..self.symbols.len()).map(Node::Symbol));
+        let mut components = Vec::new();
+        for start in starts {
+            let seen = match start {
+                Node::Number(n) => seen_numbers[n],
+                Node::Symbol(s) => seen_symbols[s],
+            };
+            if seen {
+                continue;
+            }
+            let reached = search::bfs(
+                [start],
+                |node| match *node {
+                    Node::Number(n) => self.symbols_around[n]
+                        .iter()
+                        .map(|&s| Node::Symbol(s))
+                        .collect::<Vec<_>>(),
+                    Node::Symbol(s) => self.numbers_around[s]
+                        .iter()
+                        .map(|&n| Node::Number(n))
+                        .collect(),
+                },
+                |_| false,
+            );
+            let mut component = Component::default();
+            for (node, _) in reached.iter() {
+                match *node {
+                    Node::Number(n) => {
+                        seen_numbers[n] = true;
+                        component.numbers.push(n);
+                    }
+                    Node::Symbol(s) => {
+                        seen_symbols[s] = true;
+                        component.symbols.push(s);
+                    }
                 }
             }
+            component.numbers.sort_unstable();
+            component.symbols.sort_unstable();
+            components.push(component);
         }
-        false
+        components
     }
-    fn get_stars(&self, parsed_number: &ParsedNumber) -> Option<Vec<(usize, usize)>> {
-        let res: Vec<(usize, usize)> = parsed_number
-            .get_surround()
-            .into_iter()
-            .filter(|&pos| Some('*') == self.get(pos))
-            .collect();
-        if res.len() == 0 {
-            None
-        } else {
-            Some(res)
-        }
+
+    /// The symbols following `rule`.
+    fn gears(&self, rule: GearRule) -> impl Iterator<Item = usize> + '_ {
+        (0..self.symbols.len()).filter(move |&s| {
+            rule.char.is_none_or(|c| c == self.symbols[s].char)
+                && self.numbers_around[s].len() == rule.neighbours
+        })
     }
-}
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let engine = Engine::from(input);
-    let parser = NumberParser::new(&engine);
-    let mut sum = 0;
-    for parsed_number in parser {
-        if engine.is_part(&parsed_number) {
-            sum += parsed_number.number();
-        }
+    /// Sum of the gear ratios, `combine` making the ratio out of the numbers next to a gear.
+    fn gear_ratio_sum(&self, rule: GearRule, combine: impl Fn(&[u32]) -> u32) -> u32 {
+        self.gears(rule)
+            .map(|s| {
+                let values: Vec<u32> = self.numbers_of(s).map(|number| number.value).collect();
+                combine(&values)
+            })
+            .sum()
     }
-    Some(sum)
-}
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let engine = Engine::from(input);
-    let parser = NumberParser::new(&engine);
-    let mut hash_map: HashMap<(usize, usize), Vec<ParsedNumber>> = HashMap::new();
-
-    let mut sum = 0;
-
-    for parsed_number in parser {
-        if let Some(stars) = engine.get_stars(&parsed_number) {
-            for star_pos in stars {
-                hash_map
-                    .entry(star_pos)
-                    .and_modify(|v| v.push(parsed_number.clone()))
-                    .or_insert(vec![parsed_number.clone()]);
+    /// The schematic with part numbers in green, other numbers in red, and gears of `rule`
+    /// in yellow among the other symbols.
+    fn annotated(&self, rule: GearRule) -> String {
+        let mut colors = self.map.map(|_| Rgb::gray(110));
+        for (n, number) in self.numbers.iter().enumerate() {
+            let color = if self.is_part(n) {
+                Rgb::new(80, 220, 100)
+            } else {
+                Rgb::new(230, 70, 70)
+            };
+            let (x, y) = number.start;
+            for dx in 0..number.length {
+                colors[(x + dx, y)] = color;
             }
         }
+        for symbol in &self.symbols {
+            colors[symbol.pos] = Rgb::WHITE;
+        }
+        for s in self.gears(rule) {
+            colors[self.symbols[s].pos] = Rgb::new(250, 210, 50);
+        }
+        Frame::from_grid(&self.map, |pos, &c| Cell::new(c, colors[pos])).to_ansi()
     }
-    for (_star_pos, v) in hash_map {
-        if v.len() == 2 {
-            sum += v[0].number() * v[1].number();
+
+    /// A summary of the schematic, after the annotated schematic.
+    fn describe(&self, rule: GearRule) -> String {
+        let parts = self.part_numbers().count();
+        let components = self.components();
+        let largest = components
+            .iter()
+            .max_by_key(|component| component.numbers.len() + component.symbols.len());
+        let mut text = self.annotated(rule);
+        text += &format!(
+            "{parts} part numbers, {} other numbers, {} symbols, {} gears of ratios adding up to {}\n",
+            self.numbers.len() - parts,
+            self.symbols.len(),
+            self.gears(rule).count(),
+            self.gear_ratio_sum(rule, |values| values.iter().product()),
+        );
+        text += &format!("{} connected components", components.len());
+        if let Some(largest) = largest {
+            let symbols: String = largest
+                .symbols
+                .iter()
+                .map(|&s| self.symbols[s].char)
+                .collect();
+            text += &format!(
+                ", the largest with {} numbers and the symbols {symbols:?}",
+                largest.numbers.len()
+            );
         }
+        text + "\n"
     }
-    Some(sum)
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    Some(scan(input)?.part_numbers)
+}
+
+pub fn part_two(input: &str) -> Option<u32> {
+    Some(scan(input)?.gear_ratios)
+}
+
+/// Prints the annotated schematic with `--annotate`.
+fn extras(input: &str, options: &Options) {
+    if options.flag("--annotate") {
+        print!("{}", Schematic::from(input).describe(STAR_GEARS));
+    }
+}
+
+fn scan(input: &str) -> Option<Totals> {
+    Scanner::new(input.as_bytes()).scan().ok()
 }
 
 #[cfg(test)]
@@ -226,4 +423,137 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(467835));
     }
+
+    fn example() -> Schematic {
+        Schematic::from(advent_of_code::template::read_file("examples", DAY).as_str())
+    }
+
+    #[test]
+    fn test_adjacency_queries() {
+        let schematic = example();
+        assert_eq!(schematic.numbers.len(), 10);
+        assert_eq!(schematic.symbols.len(), 6);
+        // 114 and 58 are not part numbers.
+        let others: Vec<u32> = (0..schematic.numbers.len())
+            .filter(|&n| !schematic.is_part(n))
+            .map(|n| schematic.numbers[n].value)
+            .collect();
+        assert_eq!(others, [114, 58]);
+        let symbols: Vec<Symbol> = schematic.symbols_of(0).copied().collect();
+        assert_eq!(
+            symbols,
+            [Symbol {
+                char: '*',
+                pos: (3, 1)
+            }]
+        );
+        let numbers: Vec<u32> = schematic.numbers_of(0).map(|n| n.value).collect();
+        assert_eq!(numbers, [467, 35]);
+    }
+
+    #[test]
+    fn test_components() {
+        let schematic = example();
+        let components = schematic.components();
+        // 467-*-35, 633-#, 617-*, +-592, 755-*-598, 664-$, and 114 and 58 alone.
+        assert_eq!(components.len(), 8);
+        let linked: Vec<&Component> = components.iter().filter(|c| c.numbers.len() == 2).collect();
+        assert_eq!(linked.len(), 2);
+        assert!(components.contains(&Component {
+            numbers: vec![1],
+            symbols: vec![],
+        }));
+        let input = "1*2\n.+.\n3.4\n";
+        assert_eq!(Schematic::from(input).components().len(), 1);
+    }
+
+    #[test]
+    fn test_gear_rules() {
+        let schematic = example();
+        assert_eq!(schematic.gears(STAR_GEARS).count(), 2);
+        let lonely_stars = GearRule {
+            char: Some('*'),
+            neighbours: 1,
+        };
+        assert_eq!(schematic.gear_ratio_sum(lonely_stars, |v| v[0]), 617);
+        let any_single = GearRule {
+            char: None,
+            neighbours: 1,
+        };
+        assert_eq!(
+            schematic.gear_ratio_sum(any_single, |v| v[0]),
+            617 + 633 + 592 + 664
+        );
+        let triple = Schematic::from("2.3\n.*.\n..4\n");
+        let three_star = GearRule {
+            char: Some('*'),
+            neighbours: 3,
+        };
+        assert_eq!(triple.gear_ratio_sum(three_star, |v| v.iter().sum()), 9);
+        assert_eq!(triple.gear_ratio_sum(STAR_GEARS, |v| v.iter().product()), 0);
+    }
+
+    #[test]
+    fn test_annotated() {
+        let annotated = Schematic::from("12..\n.*.3\n").annotated(STAR_GEARS);
+        let lines: Vec<&str> = annotated.split("\r\n").collect();
+        assert_eq!(
+            lines[0],
+            "\x1b[38;2;80;220;100m12\x1b[38;2;110;110;110m..\x1b[0m"
+        );
+        assert!(lines[1].contains("\x1b[38;2;255;255;255m*"));
+        assert!(lines[1].ends_with("\x1b[38;2;230;70;70m3\x1b[0m"));
+    }
+
+    /// Both parts the way the schematic model finds them.
+    fn schematic_totals(input: &str) -> Totals {
+        let schematic = Schematic::from(input);
+        Totals {
+            part_numbers: schematic.part_numbers().map(|number| number.value).sum(),
+            gear_ratios: schematic.gear_ratio_sum(STAR_GEARS, |values| values.iter().product()),
+        }
+    }
+
+    #[test]
+    fn test_scan_edges() {
+        // numbers against the borders, CRLF line ends, no final line end, and a number above a
+        // star covering both diagonals, counted once.
+        let input = "12*3\r\n....\r\n2*2.\r\n....\r\n.777\r\n..*.\r\n...5";
+        assert_eq!(
+            scan(input),
+            Some(Totals {
+                part_numbers: 12 + 3 + 2 + 2 + 777 + 5,
+                gear_ratios: 12 * 3 + 2 * 2 + 777 * 5,
+            })
+        );
+        // three numbers around a star.
+        assert_eq!(
+            scan("1.1\n.*.\n1..\n"),
+            Some(Totals {
+                part_numbers: 3,
+                gear_ratios: 0
+            })
+        );
+        assert_eq!(
+            scan("123\n.*.\n"),
+            Some(Totals {
+                part_numbers: 123,
+                gear_ratios: 0
+            })
+        );
+        assert_eq!(scan(""), Some(Totals::default()));
+    }
+
+    #[test]
+    fn test_scan_matches_schematic() {
+        for size in [1, 5, 40, 140] {
+            for seed in 0..5 {
+                let input = advent_of_code::generate::generate(DAY, size, seed).unwrap();
+                // a small buffer, so rows are read in several pieces.
+                let reader = io::BufReader::with_capacity(7, input.as_bytes());
+                let totals = Scanner::new(reader).scan().unwrap();
+                assert_eq!(totals, schematic_totals(&input), "size {size}, seed {seed}");
+            }
+        }
+    }
 }
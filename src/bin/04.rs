@@ -1,90 +1,153 @@
-use std::collections::VecDeque;
+use advent_of_code::parse::{OrReport, ParseError, Source};
+use advent_of_code::template::Options;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
-use rayon::iter::{ParallelBridge, ParallelIterator};
+advent_of_code::solution!(4, extras);
 
-advent_of_code::solution!(4);
+/// Numbers on the cards go up to 127, so that a set of them fits a `u128`.
+const MAX_NUMBER: u8 = 127;
 
 struct Card {
-    winnig: Vec<u32>,
-    numbers: Vec<u32>,
+    id: u32,
+    /// Bit `n` is set for the number `n`.
+    winning: u128,
+    numbers: u128,
 }
 
-impl From<&str> for Card {
-    fn from(value: &str) -> Self {
-        let (_card, winning_and_numbers) = value.split_once(':').unwrap();
-        let (winning, numbers) = winning_and_numbers.split_once('|').unwrap();
-
-        Card {
-            winnig: winning
-                .split(' ')
-                .filter(|n| !n.is_empty())
-                .map(|n| u32::from_str_radix(n, 10).unwrap())
-                .collect(),
-            numbers: numbers
-                .split(' ')
-                .filter(|n| !n.is_empty())
-                .map(|n| u32::from_str_radix(n, 10).unwrap())
-                .collect(),
+impl Card {
+    fn parse<'a>(source: &Source<'a>, line: &'a str) -> Result<Self, ParseError> {
+        let (card, winning_and_numbers) = source.key_value(line)?;
+        let id = card
+            .strip_prefix("Card")
+            .ok_or_else(|| source.error(card, "expected \"Card <id>\""))?;
+        let (winning, numbers) = source.split_once(winning_and_numbers, "|")?;
+
+        Ok(Card {
+            id: source.number(id)?,
+            winning: parse_set(source, winning)?,
+            numbers: parse_set(source, numbers)?,
+        })
+    }
+
+    /// How many of our numbers are winning ones.
+    fn matches(&self) -> u32 {
+        (self.winning & self.numbers).count_ones()
+    }
+
+    fn score(&self) -> u32 {
+        match self.matches() {
+            0 => 0,
+            matches => 1 << (matches - 1),
         }
     }
 }
 
-impl Card {
-    fn score(&self) -> u32 {
-        let mut score = 0;
-        for n in &self.numbers {
-            if self.winnig.contains(n) {
-                if score == 0 {
-                    score = 1;
-                } else {
-                    score *= 2;
-                }
+/// Parses numbers separated by whitespace into a set, rejecting duplicates and numbers above
+/// [`MAX_NUMBER`].
+fn parse_set<'a>(source: &Source<'a>, fragment: &'a str) -> Result<u128, ParseError> {
+    let mut set = 0_u128;
+    for token in fragment.split_whitespace() {
+        let number: u8 = source.number(token)?;
+        if number > MAX_NUMBER {
+            return Err(source.error(token, format!("numbers go up to {MAX_NUMBER}")));
+        }
+        if set & (1 << number) != 0 {
+            return Err(source.error(token, format!("{number} is already on the card")));
+        }
+        set |= 1 << number;
+    }
+    Ok(set)
+}
+
+fn parse_cards(input: &str) -> Result<Vec<Card>, ParseError> {
+    let source = Source::new(input);
+    source
+        .lines()
+        .map(|line| Card::parse(&source, line))
+        .collect()
+}
+
+/// The copies of every card once all the cards have been scratched, originals included.
+/// Cards are identified by their index in the pile.
+struct Copies {
+    matches: Vec<u32>,
+    copies: Vec<u32>,
+}
+
+impl Copies {
+    fn new(cards: &[Card]) -> Self {
+        let matches: Vec<u32> = cards.iter().map(Card::matches).collect();
+        let mut copies = vec![1; cards.len()];
+        for (card, &won) in matches.iter().enumerate() {
+            let end = (card + 1 + won as usize).min(cards.len());
+            for next in card + 1..end {
+                copies[next] += copies[card];
             }
         }
-        score
+        Copies { matches, copies }
+    }
+
+    fn total(&self) -> u32 {
+        self.copies.iter().sum()
+    }
+
+    fn of(&self, card: usize) -> u32 {
+        self.copies[card]
+    }
+
+    /// The earlier cards that won copies of `card`, with how many copies each one gave, that is
+    /// its own number of copies. Together with the original, they add up to the copies of `card`.
+    fn contributors(&self, card: usize) -> impl Iterator<Item = (usize, u32)> + '_ {
+        // a card wins at most one copy of each of the next 128 cards.
+        let first = card.saturating_sub(MAX_NUMBER as usize + 1);
+        (first..card)
+            .filter(move |&earlier| earlier + self.matches[earlier] as usize >= card)
+            .map(|earlier| (earlier, self.copies[earlier]))
     }
 
-    fn score_part_2(&self) -> u32 {
-        self.numbers
-            .iter()
-            .filter(|n| self.winnig.contains(n))
-            .count() as u32
+    /// A line per card with its matches, copies and where the copies come from.
+    fn table(&self, cards: &[Card]) -> String {
+        let mut table = String::from(" Card  Matches    Copies  Won from\n");
+        for (index, card) in cards.iter().enumerate() {
+            let mut from = String::from("1 original");
+            for (earlier, copies) in self.contributors(index) {
+                from += &format!(" + {copies} from {}", cards[earlier].id);
+            }
+            table += &format!(
+                "{:>5}  {:>7}  {:>8}  {from}\n",
+                card.id,
+                self.matches[index],
+                self.of(index)
+            );
+        }
+        table + &format!("Total: {}\n", self.total())
     }
 }
 
 pub fn part_one(input: &str) -> Option<u32> {
+    let cards = parse_cards(input).or_report()?;
     Some(
-        input
-            .lines()
-            .par_bridge()
-            .map(Card::from)
+        cards
+            .par_iter()
             .map(|c| c.score())
             .reduce(|| 0, |acc, el| acc + el),
     )
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    // this is the memory of how many copies we have for following cards
-    let mut memory = VecDeque::new();
-    Some(
-        input
-            .lines()
-            .map(Card::from)
-            .map(|c| c.score_part_2())
-            .fold((&mut memory, 0), |(memory, cc), score| {
-                let current_card_copies = memory.pop_front().unwrap_or(1);
-                for delta in 0..(score as usize) {
-                    if let Some(future_copies) = memory.get(delta) {
-                        memory[delta] = future_copies + current_card_copies;
-                    } else {
-                        // original cards + 1
-                        memory.push_back(1 + current_card_copies);
-                    }
-                }
-                (memory, cc + current_card_copies)
-            })
-            .1,
-    )
+    let cards = parse_cards(input).or_report()?;
+    Some(Copies::new(&cards).total())
+}
+
+/// Prints where the copies of every card come from with `--table`.
+fn extras(input: &str, options: &Options) {
+    if !options.flag("--table") {
+        return;
+    }
+    // parse errors were already reported by the parts.
+    if let Ok(cards) = parse_cards(input) {
+        print!("{}", Copies::new(&cards).table(&cards));
+    }
 }
 
 #[cfg(test)]
@@ -102,4 +165,29 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(30));
     }
+
+    #[test]
+    fn test_copies() {
+        let cards = parse_cards(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let copies = Copies::new(&cards);
+        let counts: Vec<u32> = (0..cards.len()).map(|card| copies.of(card)).collect();
+        assert_eq!(counts, [1, 2, 4, 8, 14, 1]);
+        let contributors: Vec<(usize, u32)> = copies.contributors(4).collect();
+        assert_eq!(contributors, [(0, 1), (2, 4), (3, 8)]);
+        assert_eq!(copies.contributors(0).count(), 0);
+        assert_eq!(copies.contributors(5).count(), 0);
+        let table = copies.table(&cards);
+        assert!(table
+            .contains("    5        0        14  1 original + 1 from 1 + 4 from 3 + 8 from 4\n"));
+        assert!(table.ends_with("Total: 30\n"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = parse_cards("Card 1: 1 2 | 3 128\n").err().unwrap();
+        assert_eq!((error.line, error.column), (1, 17));
+        let error = parse_cards("Card 1: 1 2 | 3 3\n").err().unwrap();
+        assert_eq!(error.column, 17);
+        assert!(parse_cards("Carte 1: 1 | 2\n").is_err());
+    }
 }
@@ -1,294 +1,570 @@
-use std::cmp::Ordering;
+use std::iter;
 
-use nom::{
-    bytes::complete::tag,
-    character::complete::{alpha1, digit1, line_ending},
-    combinator::{map, opt},
-    multi::{many1, separated_list1},
-    sequence::{delimited, pair, terminated, tuple},
-    IResult,
-};
+use advent_of_code::parse::{OrReport, ParseError, Source};
+use advent_of_code::ranges::{IntervalSet, Piece, PiecewiseMap};
+use advent_of_code::search;
+use advent_of_code::template::Options;
 
-advent_of_code::solution!(5);
+advent_of_code::solution!(5, extras);
+
+const SEED: &str = "seed";
+const LOCATION: &str = "location";
 
 #[derive(Debug)]
 struct Almanac<'a> {
     seeds: Vec<u64>,
+    /// The `seeds: ` line, to point at in diagnostics.
+    seeds_line: &'a str,
     maps: Vec<AlmanacMap<'a>>,
 }
 
-impl<'a> From<&'a str> for Almanac<'a> {
-    fn from(input: &'a str) -> Self {
-        fn parse_seeds(input: &str) -> IResult<&str, Vec<u64>> {
-            delimited(
-                tag("seeds: "),
-                separated_list1(
-                    tag(" "),
-                    map(digit1, |d| u64::from_str_radix(d, 10).unwrap()),
-                ),
-                line_ending,
-            )(input)
+impl<'a> Almanac<'a> {
+    /// Parses the `seeds: ` line then the maps, a block each.
+    fn parse(source: &Source<'a>) -> Result<Self, ParseError> {
+        let mut blocks = source.blocks();
+        let Some(seeds_block) = blocks.next() else {
+            return Err(source.error_at(0, 1, "expected a \"seeds\" line"));
+        };
+        let mut seeds_lines = seeds_block.lines();
+        let seeds_line = seeds_lines.next().unwrap_or(seeds_block);
+        if let Some(extra) = seeds_lines.next() {
+            return Err(source.error(extra, "expected a blank line after the seeds"));
         }
+        let seeds = source.numbers(source.expect_key(seeds_line, "seeds")?)?;
 
-        fn parse_map_info(input: &str) -> IResult<&str, (&str, &str)> {
-            map(
-                tuple((alpha1, tag("-to-"), alpha1, tag(" map:"), line_ending)),
-                |(source, _, destination, _, _)| (source, destination),
-            )(input)
+        let maps = blocks
+            .map(|block| AlmanacMap::parse(source, block))
+            .collect::<Result<Vec<_>, _>>()?;
+        if maps.is_empty() {
+            let end = source.text().trim_end().len();
+            return Err(source.error_at(end, 1, "expected a map after the seeds"));
         }
+        Ok(Almanac {
+            seeds,
+            seeds_line,
+            maps,
+        })
+    }
 
-        fn parse_range(input: &str) -> IResult<&str, MapRange> {
-            map(
-                tuple((digit1, tag(" "), digit1, tag(" "), digit1, line_ending)),
-                |(destination, _, source, _, lenght, _)| MapRange {
-                    source_start: u64::from_str_radix(source, 10).unwrap(),
-                    dest_start: u64::from_str_radix(destination, 10).unwrap(),
-                    length: u64::from_str_radix(lenght, 10).unwrap(),
-                },
-            )(input)
-        }
-        fn parse_map(input: &str) -> IResult<&str, AlmanacMap> {
-            map(
-                terminated(pair(parse_map_info, many1(parse_range)), opt(line_ending)),
-                |((source, destination), ranges)| AlmanacMap {
-                    source: source,
-                    destination: destination,
-                    map_ranges: ranges,
-                },
-            )(input)
-        }
+    /// For part 2, seeds come as `start length` pairs.
+    fn seed_ranges(&self) -> IntervalSet {
+        self.seeds
+            .chunks_exact(2)
+            .map(|pair| pair[0]..pair[0].saturating_add(pair[1]))
+            .collect()
+    }
+
+    /// The map out of `category`, if any.
+    fn map_from(&self, category: &str) -> Option<&AlmanacMap<'a>> {
+        self.maps.iter().find(|m| m.source == category)
+    }
 
-        fn parse_almanach(input: &str) -> IResult<&str, Almanac> {
-            map(
-                tuple((parse_seeds, line_ending, many1(parse_map))),
-                |(seeds, _, maps)| Almanac { seeds, maps },
-            )(input)
+    /// The map into `category`, if any.
+    fn map_to(&self, category: &str) -> Option<&AlmanacMap<'a>> {
+        self.maps.iter().find(|m| m.destination == category)
+    }
+
+    /// The maps to follow from `from` to `to`, in order, [`None`] if `to` can't be reached.
+    fn chain(&self, from: &str, to: &str) -> Option<Vec<&AlmanacMap<'a>>> {
+        let mut chain = Vec::new();
+        let mut category = from;
+        while category != to {
+            // following more maps than there are means going around a cycle.
+            if chain.len() == self.maps.len() {
+                return None;
+            }
+            let valid_map = self.map_from(category)?;
+            chain.push(valid_map);
+            category = valid_map.destination;
         }
-        let (_left, alm) = parse_almanach(input).unwrap();
-        alm
+        Some(chain)
     }
-}
 
-struct AlmanacPart2<'a> {
-    seed_ranges: Vec<(u64, u64)>,
-    maps: Vec<AlmanacMap<'a>>,
-}
+    /// A single map from `from` to `to`, composing the maps in between.
+    fn compose(&self, from: &str, to: &str) -> Option<PiecewiseMap> {
+        let chain = self.chain(from, to)?;
+        Some(
+            chain
+                .into_iter()
+                .fold(PiecewiseMap::new(), |composed, valid_map| {
+                    composed.then(&valid_map.map)
+                }),
+        )
+    }
 
-impl<'a> From<Almanac<'a>> for AlmanacPart2<'a> {
-    fn from(value: Almanac<'a>) -> Self {
-        let mut seed_ranges = Vec::new();
-        for i in 0..value.seeds.len() / 2 {
-            seed_ranges.push((
-                value.seeds[2 * i],
-                value.seeds[2 * i] + value.seeds[2 * i + 1],
-            ));
+    /// What `value` of `category` becomes in the later categories, and which values of the
+    /// earlier categories become it. Like the ranges of the maps, the earlier values stop before
+    /// `u64::MAX`.
+    fn query(&self, category: &str, value: u64) -> String {
+        let mut lines = vec![format!("{category} {value}")];
+        let mut current = category;
+        let mut image = value;
+        for _ in 0..self.maps.len() {
+            let Some(valid_map) = self.map_from(current) else {
+                break;
+            };
+            image = valid_map.map.apply(image);
+            current = valid_map.destination;
+            lines.push(format!("  -> {current} {image}"));
         }
-        AlmanacPart2 {
-            seed_ranges,
-            maps: value.maps,
+        let mut current = category;
+        let mut preimage = IntervalSet::from_ranges(iter::once(value..value.saturating_add(1)));
+        for _ in 0..self.maps.len() {
+            let Some(valid_map) = self.map_to(current) else {
+                break;
+            };
+            preimage = valid_map.map.preimage(&preimage);
+            current = valid_map.source;
+            lines.push(format!("  <- {current} {}", format_set(&preimage)));
         }
+        lines.join("\n") + "\n"
     }
-}
-#[derive(Debug)]
-struct MapRange {
-    source_start: u64,
-    dest_start: u64,
-    length: u64,
-}
 
-impl MapRange {
-    fn try_map(&self, input: u64) -> Option<u64> {
-        if input >= self.source_start && input < self.source_start + self.length {
-            let s = self.source_start as i64;
-            let d = self.dest_start as i64;
-            let delta: i64 = d - s;
-            Some((input as i64 + delta) as u64)
+    /// Checks everything the solution relies on, reporting all the problems at once: the ranges
+    /// of a map don't overlap and don't go past `u64::MAX`, and the maps make a single chain
+    /// from seed to location through every category.
+    fn validate(&self, source: &Source<'a>) -> Result<(), Vec<ParseError>> {
+        let mut errors = Vec::new();
+        for pair in self.seeds.chunks_exact(2) {
+            if pair[0].checked_add(pair[1]).is_none() {
+                let message = format!("seed range {} + {} goes past u64::MAX", pair[0], pair[1]);
+                errors.push(source.error(self.seeds_line, message));
+            }
+        }
+        for almanac_map in &self.maps {
+            almanac_map.validate(source, &mut errors);
+        }
+        self.validate_categories(source, &mut errors);
+        if errors.is_empty() {
+            Ok(())
         } else {
-            None
+            Err(errors)
         }
     }
-    fn apply_delta_range(&self, range: (u64, u64)) -> (u64, u64) {
-        let delta: i64 = self.dest_start as i64 - self.source_start as i64;
-        (
-            (range.0 as i64 + delta) as u64,
-            (range.1 as i64 + delta) as u64,
-        )
-    }
-    fn try_map_range(&self, range: (u64, u64)) -> Option<((u64, u64), [Option<(u64, u64)>; 2])> {
-        match (
-            range.0.cmp(&self.source_start),
-            range.1.cmp(&(self.source_start + self.length)),
-        ) {
-            (Ordering::Equal | Ordering::Greater, Ordering::Less | Ordering::Equal) => {
-                // range included in this MapRange
-                return Some((self.apply_delta_range((range.0, range.1)), [None, None]));
-            }
-            (Ordering::Equal | Ordering::Greater, Ordering::Greater) => {
-                // range is on right part of MapRange (can have superposition)
-                if range.0 >= self.source_start + self.length {
-                    // totally out
-                    return None;
-                } else {
-                    // range.0 < self.source_start + self.length => superposition
-                    return Some((
-                        self.apply_delta_range((range.0, self.source_start + self.length - 1)),
-                        [None, Some((self.source_start + self.length, range.1))],
-                    ));
+
+    fn validate_categories(&self, source: &Source<'a>, errors: &mut Vec<ParseError>) {
+        for (i, almanac_map) in self.maps.iter().enumerate() {
+            for earlier in &self.maps[..i] {
+                let line = source.line_of(earlier.header);
+                if earlier.source == almanac_map.source {
+                    let message = format!("second map from {}, after line {line}", earlier.source);
+                    errors.push(source.error(almanac_map.header, message));
+                }
+                if earlier.destination == almanac_map.destination {
+                    let message =
+                        format!("second map to {}, after line {line}", earlier.destination);
+                    errors.push(source.error(almanac_map.header, message));
                 }
             }
-            (Ordering::Less, Ordering::Equal | Ordering::Less) => {
-                // range is on left part of MapRange (can have superposition)
-                if range.1 < self.source_start {
-                    // totally out
-                    return None;
-                } else {
-                    // range.1 >= self.source_start => superposition
-                    return Some((
-                        self.apply_delta_range((self.source_start, range.1)),
-                        [Some((range.0, self.source_start - 1)), None],
-                    ));
+        }
+
+        // cycles, reported on their first map.
+        for (i, almanac_map) in self.maps.iter().enumerate() {
+            let mut cycle = vec![almanac_map.source];
+            let mut current = almanac_map;
+            while cycle.len() <= self.maps.len() && current.destination != almanac_map.source {
+                cycle.push(current.destination);
+                match self.map_from(current.destination) {
+                    Some(next) => current = next,
+                    None => break,
                 }
             }
-            (Ordering::Less, Ordering::Greater) => {
-                // range is overset of MapRange
-                return Some((
-                    self.apply_delta_range((
-                        self.source_start,
-                        self.source_start + self.length - 1,
-                    )),
-                    [
-                        Some((range.0, self.source_start - 1)),
-                        Some((self.source_start + self.length, range.1)),
-                    ],
-                ));
+            let first = self
+                .maps
+                .iter()
+                .position(|m| cycle.contains(&m.source))
+                .unwrap_or(i);
+            if current.destination == almanac_map.source && first == i {
+                cycle.push(almanac_map.source);
+                let message = format!("the maps go around a cycle: {}", cycle.join(" -> "));
+                errors.push(source.error(almanac_map.header, message));
+            }
+        }
+
+        let reachable = search::bfs(
+            [SEED],
+            |&category| {
+                self.maps
+                    .iter()
+                    .filter(move |m| m.source == category)
+                    .map(|m| m.destination)
+            },
+            |_| false,
+        );
+        for almanac_map in &self.maps {
+            if reachable.cost_to(&almanac_map.source).is_none() {
+                let message = format!("{} can't be reached from {SEED}", almanac_map.source);
+                errors.push(source.error(almanac_map.header, message));
+            }
+        }
+        if reachable.cost_to(&LOCATION).is_none() {
+            let message = format!("{LOCATION} can't be reached from {SEED}");
+            let last = self.chain_end(SEED).map_or(self.seeds_line, |m| m.header);
+            errors.push(source.error(last, message));
+        }
+    }
+
+    /// The last map following the maps from `category`, before a dead end or a category seen
+    /// before.
+    fn chain_end(&self, category: &str) -> Option<&AlmanacMap<'a>> {
+        let mut seen = vec![category];
+        let mut last = self.map_from(category)?;
+        while let Some(next) = self.map_from(last.destination) {
+            if seen.contains(&next.source) {
+                break;
             }
+            seen.push(next.source);
+            last = next;
         }
+        Some(last)
     }
 }
 
+/// Ranges as `start..end`, separated by commas.
+fn format_set(set: &IntervalSet) -> String {
+    if set.is_empty() {
+        return "nothing".into();
+    }
+    let ranges: Vec<String> = set
+        .iter()
+        .map(|range| format!("{}..{}", range.start, range.end))
+        .collect();
+    ranges.join(", ")
+}
+
+/// Category names are made of letters, like `seed` or `light`.
+fn is_category(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphabetic())
+}
+
 #[derive(Debug)]
 struct AlmanacMap<'a> {
     source: &'a str,
     destination: &'a str,
-    map_ranges: Vec<MapRange>,
+    /// The `source-to-destination map:` line.
+    header: &'a str,
+    ranges: Vec<MapRange<'a>>,
+    map: PiecewiseMap,
 }
 
 impl<'a> AlmanacMap<'a> {
-    fn map(&self, input: u64) -> u64 {
-        self.map_ranges
-            .iter()
-            .find_map(|range| range.try_map(input))
-            .unwrap_or(input)
+    /// Parses a `<source>-to-<destination> map:` header followed by its ranges.
+    fn parse(source: &Source<'a>, block: &'a str) -> Result<Self, ParseError> {
+        let mut lines = block.lines();
+        let header = lines.next().unwrap_or(block);
+        let expected_header = || {
+            source.error(
+                header,
+                "expected a \"<source>-to-<destination> map:\" header",
+            )
+        };
+        let (from, to) = header
+            .strip_suffix(" map:")
+            .and_then(|name| name.split_once("-to-"))
+            .filter(|(from, to)| [from, to].iter().all(|c| is_category(c)))
+            .ok_or_else(expected_header)?;
+
+        let ranges = lines
+            .map(|line| MapRange::parse(source, line))
+            .collect::<Result<Vec<_>, _>>()?;
+        if ranges.is_empty() {
+            return Err(source.error(header, "expected ranges after the header"));
+        }
+        Ok(AlmanacMap {
+            source: from,
+            destination: to,
+            header,
+            map: PiecewiseMap::from_pieces(ranges.iter().map(MapRange::piece)),
+            ranges,
+        })
     }
 
-    fn map_range(&self, input_range: (u64, u64)) -> Vec<(u64, u64)> {
-        let mut mapped_vec: Vec<(u64, u64)> = Vec::new();
-        let mut to_map = vec![input_range];
-
-        for map_range in &self.map_ranges {
-            let mut new_to_map = Vec::new();
-            for range in to_map {
-                if let Some((mapped, not_mapped_array)) = map_range.try_map_range(range) {
-                    mapped_vec.push(mapped);
-                    for not_mapped in not_mapped_array {
-                        if let Some(not_mapped_range) = not_mapped {
-                            new_to_map.push(not_mapped_range);
-                        }
-                    }
-                } else {
-                    new_to_map.push(range);
+    /// Reports ranges going past `u64::MAX` and overlapping source ranges.
+    fn validate(&self, source: &Source<'a>, errors: &mut Vec<ParseError>) {
+        for range in &self.ranges {
+            for (name, start) in [("source", range.source), ("destination", range.destination)] {
+                if start.checked_add(range.length).is_none() {
+                    let message =
+                        format!("{name} range {start} + {} goes past u64::MAX", range.length);
+                    errors.push(source.error(range.line, message));
                 }
             }
-            to_map = new_to_map;
         }
 
-        mapped_vec.append(&mut to_map);
-        mapped_vec
+        let mut sorted: Vec<&MapRange> = self.ranges.iter().filter(|r| r.length > 0).collect();
+        sorted.sort_by_key(|range| range.source);
+        // the range reaching the furthest so far, any later one starting before its end overlaps.
+        let mut furthest: Option<&MapRange> = None;
+        for range in sorted {
+            if let Some(previous) = furthest.filter(|p| p.source_end() > range.source) {
+                // reported on the line coming last.
+                let (first, second) = if source.line_of(previous.line) < source.line_of(range.line)
+                {
+                    (previous, range)
+                } else {
+                    (range, previous)
+                };
+                let message = format!(
+                    "source range {}..{} overlaps {}..{} from line {}",
+                    second.source,
+                    second.source_end(),
+                    first.source,
+                    first.source_end(),
+                    source.line_of(first.line)
+                );
+                errors.push(source.error(second.line, message));
+            }
+            if furthest.is_none_or(|p| range.source_end() > p.source_end()) {
+                furthest = Some(range);
+            }
+        }
     }
 }
-pub fn part_one(input: &str) -> Option<u64> {
-    let almanac = Almanac::from(input);
 
-    let mut state = ("seed", almanac.seeds.clone());
+/// A `destination source length` line of a map.
+#[derive(Debug)]
+struct MapRange<'a> {
+    line: &'a str,
+    destination: u64,
+    source: u64,
+    length: u64,
+}
+
+impl<'a> MapRange<'a> {
+    /// Parses a `<destination> <source> <length>` line.
+    fn parse(source: &Source<'a>, line: &'a str) -> Result<Self, ParseError> {
+        match source.numbers(line)?[..] {
+            [destination, start, length] => Ok(MapRange {
+                line,
+                destination,
+                source: start,
+                length,
+            }),
+            _ => Err(source.error(line, "expected a \"<destination> <source> <length>\" range")),
+        }
+    }
 
-    while state.0 != "location" {
-        let Some(valid_map) = almanac.maps.iter().find(|&m| m.source == state.0) else {
-            panic!();
-        };
-        state = (
-            valid_map.destination,
-            state.1.into_iter().map(|inp| valid_map.map(inp)).collect(),
-        );
+    fn source_end(&self) -> u64 {
+        self.source.saturating_add(self.length)
     }
 
-    state.1.into_iter().min()
+    /// The range as a piece, cut where it would go past `u64::MAX`.
+    fn piece(&self) -> Piece {
+        let length = self
+            .length
+            .min(u64::MAX - self.source)
+            .min(u64::MAX - self.destination);
+        Piece {
+            source: self.source..self.source + length,
+            destination: self.destination,
+        }
+    }
 }
-fn merge_ranges(r1: (u64, u64), r2: (u64, u64)) -> Option<(u64, u64)> {
-    assert!(r1.1 >= r1.0);
-    assert!(r2.1 >= r2.0);
-    if r2.1 < r1.0 || r1.1 < r2.0 {
-        None
-    } else {
-        Some((r1.0.min(r2.0), r1.1.max(r2.1)))
+
+/// Answers `--query "<category> <value>"`.
+fn extras(input: &str, options: &Options) {
+    let Some(query) = options.value("--query") else {
+        return;
+    };
+    // problems with the almanac were already reported by the parts.
+    let source = Source::new(input);
+    let Ok(almanac) = Almanac::parse(&source) else {
+        return;
+    };
+    if almanac.validate(&source).is_err() {
+        return;
+    }
+    let parsed = query
+        .split_once(' ')
+        .and_then(|(category, value)| Some((category, value.trim().parse().ok()?)));
+    match parsed {
+        Some((category, _))
+            if !almanac
+                .maps
+                .iter()
+                .any(|m| m.source == category || m.destination == category) =>
+        {
+            eprintln!("No map from or to {category:?}");
+        }
+        Some((category, value)) => print!("{}", almanac.query(category, value)),
+        None => eprintln!("Invalid query {query:?}, expected \"<category> <value>\""),
     }
 }
 
-fn simplify_ranges(mut ranges_vec: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
-    let mut new_vec = Vec::new();
+pub fn part_one(input: &str) -> Option<u64> {
+    let source = Source::new(input);
+    let almanac = Almanac::parse(&source).or_report()?;
+    almanac.validate(&source).or_report()?;
+    let seed_to_location = almanac.compose(SEED, LOCATION)?;
 
-    new_vec.push(ranges_vec.pop().unwrap());
+    almanac
+        .seeds
+        .iter()
+        .map(|&seed| seed_to_location.apply(seed))
+        .min()
+}
 
-    for range1 in ranges_vec {
-        let mut range1_added = false;
-        for range2 in new_vec.iter_mut() {
-            if let Some(merged) = merge_ranges(range1, *range2) {
-                *range2 = merged;
-                range1_added = true;
-                break;
+pub fn part_two(input: &str) -> Option<u64> {
+    let source = Source::new(input);
+    let almanac = Almanac::parse(&source).or_report()?;
+    almanac.validate(&source).or_report()?;
+
+    almanac
+        .compose(SEED, LOCATION)?
+        .min_over(&almanac.seed_ranges())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Display;
+
+    use advent_of_code::property::{self, shrink_u64, shrink_vec, Arbitrary};
+    use advent_of_code::random::Rng;
+
+    use super::*;
+
+    /// `(destination, source, length)` triples with disjoint source ranges.
+    type MapCase = Vec<(u64, u64, u64)>;
+
+    #[derive(Clone)]
+    struct AlmanacCase {
+        /// `(start, length)` pairs, read as plain seeds for part one.
+        seeds: Vec<(u64, u64)>,
+        maps: Vec<MapCase>,
+    }
+
+    impl AlmanacCase {
+        fn category(&self, index: usize) -> String {
+            match index {
+                0 => "seed".into(),
+                i if i == self.maps.len() => "location".into(),
+                i => ((b'a' + i as u8) as char).to_string(),
             }
         }
-        if !range1_added {
-            new_vec.push(range1);
+
+        fn location(&self, seed: u64) -> u64 {
+            self.maps.iter().fold(seed, |value, ranges| {
+                ranges
+                    .iter()
+                    .find(|&&(_, source, length)| source <= value && value < source + length)
+                    .map_or(value, |&(destination, source, _)| {
+                        destination + value - source
+                    })
+            })
         }
     }
 
-    new_vec
-}
+    impl Display for AlmanacCase {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let seeds: Vec<String> = self
+                .seeds
+                .iter()
+                .map(|(start, length)| format!("{start} {length}"))
+                .collect();
+            writeln!(f, "seeds: {}", seeds.join(" "))?;
+            for (i, ranges) in self.maps.iter().enumerate() {
+                writeln!(f)?;
+                writeln!(f, "{}-to-{} map:", self.category(i), self.category(i + 1))?;
+                for (destination, source, length) in ranges {
+                    writeln!(f, "{destination} {source} {length}")?;
+                }
+            }
+            Ok(())
+        }
+    }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let almanac = AlmanacPart2::from(Almanac::from(input));
+    impl Arbitrary for AlmanacCase {
+        fn arbitrary(rng: &mut Rng, size: usize) -> Self {
+            let max = 4 * size as u64 + 4;
+            let seeds = (0..rng.range_usize(1, 3))
+                .map(|_| (rng.range(0, max), rng.range(1, max / 2)))
+                .collect();
+            let maps = (0..rng.range_usize(1, 3))
+                .map(|_| {
+                    let mut ranges = Vec::new();
+                    let mut source = rng.range(0, 3);
+                    while ranges.is_empty() || (source < max && rng.chance(2, 3)) {
+                        let length = rng.range(1, max / 2);
+                        ranges.push((rng.range(0, max), source, length));
+                        source += length + rng.range(0, 3);
+                    }
+                    rng.shuffle(&mut ranges);
+                    ranges
+                })
+                .collect();
+            AlmanacCase { seeds, maps }
+        }
 
-    let mut state = ("seed", almanac.seed_ranges.clone());
+        fn shrink(&self) -> Vec<Self> {
+            let mut candidates: Vec<Self> = shrink_vec(&self.maps, 1, |ranges| {
+                // shrinking sources could make them overlap, only shrink destinations and lengths.
+                shrink_vec(ranges, 1, |&(destination, source, length)| {
+                    let mut smaller: Vec<_> = shrink_u64(destination, 0)
+                        .into_iter()
+                        .map(|d| (d, source, length))
+                        .collect();
+                    smaller.extend(
+                        shrink_u64(length, 1)
+                            .into_iter()
+                            .map(|l| (destination, source, l)),
+                    );
+                    smaller
+                })
+            })
+            .into_iter()
+            .map(|maps| AlmanacCase {
+                seeds: self.seeds.clone(),
+                maps,
+            })
+            .collect();
+            candidates.extend(
+                shrink_vec(&self.seeds, 1, |&(start, length)| {
+                    let mut smaller: Vec<_> = shrink_u64(start, 0)
+                        .into_iter()
+                        .map(|s| (s, length))
+                        .collect();
+                    smaller.extend(shrink_u64(length, 1).into_iter().map(|l| (start, l)));
+                    smaller
+                })
+                .into_iter()
+                .map(|seeds| AlmanacCase {
+                    seeds,
+                    maps: self.maps.clone(),
+                }),
+            );
+            candidates
+        }
+    }
 
-    while state.0 != "location" {
-        let Some(valid_map) = almanac.maps.iter().find(|&m| m.source == state.0) else {
-            panic!();
-        };
+    #[test]
+    fn test_against_brute_force() {
+        property::check(|case: &AlmanacCase| {
+            let input = case.to_string();
 
-        state = (
-            valid_map.destination,
-            simplify_ranges(
-                state
-                    .1
-                    .into_iter()
-                    .flat_map(|input_range| valid_map.map_range(input_range))
-                    .collect(),
-            ),
-        );
-    }
-    Some(
-        state
-            .1
-            .into_iter()
-            .reduce(|prec, el| if prec.0 <= el.0 { prec } else { el })
-            .unwrap()
-            .0,
-    )
-}
+            let expected = case
+                .seeds
+                .iter()
+                .flat_map(|&(start, length)| [start, length])
+                .map(|seed| case.location(seed))
+                .min();
+            let result = part_one(&input);
+            if result != expected {
+                return Err(format!("part one: expected {expected:?}, got {result:?}"));
+            }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+            let expected = case
+                .seeds
+                .iter()
+                .flat_map(|&(start, length)| start..start + length)
+                .map(|seed| case.location(seed))
+                .min();
+            let result = part_two(&input);
+            if result != expected {
+                return Err(format!("part two: expected {expected:?}, got {result:?}"));
+            }
+            Ok(())
+        });
+    }
 
     #[test]
     fn test_part_one() {
@@ -301,4 +577,138 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(46));
     }
+
+    #[test]
+    fn test_queries() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let almanac = Almanac::parse(&Source::new(&input)).unwrap();
+        let seed_to_location = almanac.compose(SEED, LOCATION).unwrap();
+        assert_eq!(seed_to_location.apply(79), 82);
+        let seeds = seed_to_location.preimage(&IntervalSet::from_ranges(iter::once(82..83)));
+        assert!(seeds.contains(79));
+        assert!(almanac.chain(LOCATION, SEED).is_none());
+        assert_eq!(almanac.compose("soil", "soil"), Some(PiecewiseMap::new()));
+        let query = almanac.query("soil", 81);
+        assert!(query.starts_with("soil 81\n  -> fertilizer 81\n"));
+        assert!(query.contains("  -> location 82\n"));
+        assert!(query.ends_with("  <- seed 79..80\n"));
+        let query = almanac.query("seed", u64::MAX);
+        assert!(query.contains("  -> location 18446744073709551615\n"));
+    }
+
+    /// The line and message of every problem found in `input`.
+    fn diagnostics(input: &str) -> Vec<(usize, String)> {
+        let source = Source::new(input);
+        let almanac = Almanac::parse(&source).unwrap();
+        match almanac.validate(&source) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.into_iter().map(|e| (e.line, e.message)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_validate_ranges() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(diagnostics(&example), []);
+
+        let input = "seeds: 1 2 18446744073709551615 1\n\n\
+                     seed-to-location map:\n\
+                     50 10 5\n\
+                     0 0 11\n\
+                     18446744073709551610 100 10\n\
+                     0 14 1\n";
+        assert_eq!(
+            diagnostics(input),
+            [
+                (
+                    1,
+                    "seed range 18446744073709551615 + 1 goes past u64::MAX".into()
+                ),
+                (
+                    6,
+                    "destination range 18446744073709551610 + 10 goes past u64::MAX".into()
+                ),
+                (5, "source range 0..11 overlaps 10..15 from line 4".into()),
+                (7, "source range 14..15 overlaps 10..15 from line 4".into()),
+            ]
+        );
+        // the first range wins, and none goes past u64::MAX.
+        let seed_to_location = Almanac::parse(&Source::new(input))
+            .unwrap()
+            .compose(SEED, LOCATION)
+            .unwrap();
+        assert_eq!(seed_to_location.apply(10), 50);
+        assert_eq!(seed_to_location.apply(104), u64::MAX - 1);
+        assert_eq!(seed_to_location.apply(105), 105);
+    }
+
+    #[test]
+    fn test_validate_categories() {
+        let input = "seeds: 1 2\n\n\
+                     seed-to-soil map:\n0 1 1\n\n\
+                     soil-to-water map:\n0 1 1\n\n\
+                     water-to-soil map:\n0 1 1\n\n\
+                     seed-to-light map:\n0 1 1\n\n\
+                     humidity-to-location map:\n0 1 1\n";
+        assert_eq!(
+            diagnostics(input),
+            [
+                (9, "second map to soil, after line 3".into()),
+                (12, "second map from seed, after line 3".into()),
+                (
+                    6,
+                    "the maps go around a cycle: soil -> water -> soil".into()
+                ),
+                (15, "humidity can't be reached from seed".into()),
+                (9, "location can't be reached from seed".into()),
+            ]
+        );
+        assert_eq!(part_one(input), None);
+        assert_eq!(part_two(input), None);
+        assert_eq!(
+            diagnostics("seeds: 1\n\nsoil-to-location map:\n0 1 1\n"),
+            [
+                (3, "soil can't be reached from seed".into()),
+                (1, "location can't be reached from seed".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input| {
+            let error = Almanac::parse(&Source::new(input)).err().unwrap();
+            (error.line, error.message)
+        };
+        assert_eq!(
+            error(
+                "seeds: 99999999999999999999
+
+seed-to-location map:
+0 1 1
+"
+            ),
+            (
+                1,
+                "invalid number \"99999999999999999999\": number too large to fit in target type"
+                    .into()
+            )
+        );
+        let mut input = advent_of_code::template::read_file("examples", DAY);
+        input += "\ngarbage-to-x map:\n";
+        assert_eq!(error(&input).0, 35);
+        assert_eq!(part_one(&input), None);
+        assert_eq!(
+            error("seeds: 1\n\nseed-to-soil map:\n0 1\n"),
+            (
+                4,
+                "expected a \"<destination> <source> <length>\" range".into()
+            )
+        );
+        assert_eq!(
+            error("seeds: 1\n\nseed to soil map:\n0 1 1\n").1,
+            "expected a \"<source>-to-<destination> map:\" header"
+        );
+        assert_eq!(error("seeds: 1\n").1, "expected a map after the seeds");
+    }
 }
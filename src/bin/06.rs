@@ -1,48 +1,39 @@
+use advent_of_code::math;
+use advent_of_code::parse::{OrReport, ParseError, Source};
+use num::BigUint;
+
 advent_of_code::solution!(6);
 
 struct RaceList {
     races: Vec<Race>,
 }
 
-impl From<&str> for RaceList {
-    fn from(value: &str) -> Self {
-        let mut lines = value.lines();
-        let times = lines.next().unwrap();
-        let distances = lines.next().unwrap();
-
-        assert!(times.contains("Time:"));
-        assert!(distances.contains("Distance:"));
-
-        let races = times
-            .split_once(':')
-            .unwrap()
-            .1
-            .split(' ')
-            .filter_map(|time| {
-                if time.len() > 0 {
-                    Some(u64::from_str_radix(time, 10).unwrap())
-                } else {
-                    None
-                }
-            })
-            .zip(
-                distances
-                    .split_once(':')
-                    .unwrap()
-                    .1
-                    .split(' ')
-                    .filter_map(|distance| {
-                        if distance.len() > 0 {
-                            Some(u64::from_str_radix(distance, 10).unwrap())
-                        } else {
-                            None
-                        }
-                    }),
-            )
+/// The values of the `Time` and `Distance` lines.
+fn parse_lines<'a>(source: &Source<'a>) -> Result<(&'a str, &'a str), ParseError> {
+    let mut lines = source.lines();
+    let mut next_line = |key| {
+        let line = lines.next().ok_or_else(|| {
+            let end = source.text().len();
+            source.error_at(end, 1, format!("expected a {key:?} line"))
+        })?;
+        source.expect_key(line, key)
+    };
+    Ok((next_line("Time")?, next_line("Distance")?))
+}
+
+impl RaceList {
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let source = Source::new(input);
+        let (times, distances) = parse_lines(&source)?;
+
+        let races = source
+            .numbers(times)?
+            .into_iter()
+            .zip(source.numbers(distances)?)
             .map(|(time, distance)| Race { time, distance })
             .collect();
 
-        RaceList { races }
+        Ok(RaceList { races })
     }
 }
 
@@ -51,59 +42,49 @@ struct Race {
     distance: u64,
 }
 
-// for part2
-impl From<&str> for Race {
-    fn from(value: &str) -> Self {
-        let mut lines = value.lines();
-        let time = lines.next().unwrap();
-        let distance = lines.next().unwrap();
-
-        assert!(time.contains("Time:"));
-        assert!(distance.contains("Distance:"));
-
-        let time = u64::from_str_radix(
-            time.split_once(':').unwrap().1.replace(' ', "").as_str(),
-            10,
-        )
-        .unwrap();
-        let distance = u64::from_str_radix(
-            distance
-                .split_once(':')
-                .unwrap()
-                .1
-                .replace(' ', "")
-                .as_str(),
-            10,
-        )
-        .unwrap();
-        Race { time, distance }
+impl Race {
+    /// Holding the button for `h` ms wins if `h·(time - h) > distance`.
+    fn count_ways_to_win(&self) -> u64 {
+        math::count_between_roots(self.time, self.distance)
     }
 }
 
-impl Race {
-    fn count_ways_to_win(&self) -> u64 {
-        let time = self.time as f64;
-        let distance = self.distance as f64;
-        let delta = (time.powf(2.)) - (4. * distance);
-        let root1 = (-(delta.sqrt()) + time) / f64::from(2);
-        let root2 = ((delta).sqrt() + time) / f64::from(2);
-        let root1_int_sup: u64 = if root1.ceil() > root1 {
-            root1.ceil()
-        } else {
-            (root1 + f64::from(1)).ceil()
-        } as u64;
-
-        let root2_int_inf: u64 = if root2.floor() < root2 {
-            root2.floor()
-        } else {
-            (root2 - f64::from(1)).floor()
-        } as u64;
-        (root2_int_inf).min(self.time - 1) - (root1_int_sup).max(1) + 1
+/// The single race of part 2, where the spaces between digits are a kerning mistake. Joining
+/// the digits can make numbers of any length.
+struct KernedRace {
+    time: BigUint,
+    distance: BigUint,
+}
+
+impl KernedRace {
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let source = Source::new(input);
+        let (times, distances) = parse_lines(&source)?;
+        // the digits as written, leading zeros of the later numbers included.
+        let concat = |fragment: &str| {
+            let digits: String = fragment.split_whitespace().collect();
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(source.error(fragment, "expected digits"));
+            }
+            Ok(digits.parse::<BigUint>().unwrap())
+        };
+        Ok(KernedRace {
+            time: concat(times)?,
+            distance: concat(distances)?,
+        })
+    }
+
+    /// Exact with `u128` arithmetic while the race fits a `u64`, with big integers past that.
+    fn count_ways_to_win(&self) -> BigUint {
+        match (u64::try_from(&self.time), u64::try_from(&self.distance)) {
+            (Ok(time), Ok(distance)) => Race { time, distance }.count_ways_to_win().into(),
+            _ => math::count_between_roots_big(&self.time, &self.distance),
+        }
     }
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
-    let race_list = RaceList::from(input);
+    let race_list = RaceList::parse(input).or_report()?;
     race_list
         .races
         .into_iter()
@@ -111,8 +92,8 @@ pub fn part_one(input: &str) -> Option<u64> {
         .reduce(|acc, el| acc * el)
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let race = Race::from(input);
+pub fn part_two(input: &str) -> Option<BigUint> {
+    let race = KernedRace::parse(input).or_report()?;
     Some(race.count_ways_to_win())
 }
 
@@ -129,6 +110,50 @@ mod tests {
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(71503));
+        assert_eq!(result, Some(71503_u32.into()));
+    }
+
+    /// Every hold time tried one by one.
+    fn brute_force(time: u64, distance: u64) -> u64 {
+        (0..=time).filter(|h| h * (time - h) > distance).count() as u64
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        for time in 0..80 {
+            for distance in 0..time * time / 4 + 2 {
+                let race = Race { time, distance };
+                assert_eq!(race.count_ways_to_win(), brute_force(time, distance));
+                let kerned = KernedRace {
+                    time: time.into(),
+                    distance: distance.into(),
+                };
+                assert_eq!(
+                    kerned.count_ways_to_win(),
+                    brute_force(time, distance).into()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_huge_races() {
+        // f64 rounds these times, the roots 3 and time - 3 are excluded.
+        let time = (1 << 60) + 1;
+        let race = Race {
+            time,
+            distance: 3 * (time - 3),
+        };
+        assert_eq!(race.count_ways_to_win(), time - 7);
+
+        // 10^25 ms, beyond u64 once kerned.
+        let input = "Time:      10000000000000 000000000000\n\
+                     Distance:  29999999999999 999999999991\n";
+        let expected: BigUint = "9999999999999999999999993".parse().unwrap();
+        assert_eq!(part_two(input), Some(expected));
+        let input = "Time:      1000000000 1000000000 1000000000\n\
+                     Distance:  0\n";
+        let time: BigUint = "100000000010000000001000000000".parse().unwrap();
+        assert_eq!(part_two(input), Some(time - 1_u32));
     }
 }
@@ -146,7 +146,7 @@ impl From<&str> for Hand {
         ];
         Hand {
             cards,
-            bid: u32::from_str_radix(bid, 10).unwrap(),
+            bid: bid.parse::<u32>().unwrap(),
         }
     }
 }
@@ -210,7 +210,7 @@ impl Card {
 
         let first_found = cards_order_2
             .iter()
-            .find(|&c| &c == &self || &c == &other)
+            .find(|&c| c == self || c == other)
             .unwrap();
         if first_found == self {
             Ordering::Greater
@@ -248,29 +248,18 @@ impl Hand {
 
 impl PartialOrd for Hand {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let ht1 = self.hand_type();
-        let ht2 = other.hand_type();
-        match ht1.cmp(&ht2) {
-            Ordering::Equal => self
-                .cards
-                .iter()
-                .zip(other.cards.iter())
-                .find_map(|(&c1, &c2)| {
-                    let comp = c1.cmp(&c2);
-                    if comp == Ordering::Equal {
-                        None
-                    } else {
-                        Some(comp)
-                    }
-                }),
-            ht_cmp => Some(ht_cmp),
-        }
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Hand {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        let ht1 = self.hand_type();
+        let ht2 = other.hand_type();
+        match ht1.cmp(&ht2) {
+            Ordering::Equal => self.cards.cmp(&other.cards),
+            ht_cmp => ht_cmp,
+        }
     }
 }
 
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 use std::collections::VecDeque;
 
+use advent_of_code::cycle::{self, Cycle};
+use advent_of_code::math;
+use itertools::Itertools;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::bytes::complete::take_until1;
@@ -9,7 +12,6 @@ use nom::combinator::{map, opt};
 use nom::multi::many1;
 use nom::sequence::{delimited, separated_pair, terminated};
 use nom::IResult;
-use num::Integer;
 use rayon::iter::ParallelBridge;
 use rayon::iter::ParallelIterator;
 advent_of_code::solution!(8);
@@ -49,7 +51,7 @@ struct InputParser;
 impl InputParser {
     fn parse<'a>(input: &'a str) -> (VecDeque<Instr>, Vec<Document<'a>>) {
         let res: IResult<&str, Vec<Instr>> = terminated(
-            many1(map(alt((char('L'), char('R'))), |a| Instr::from(a))),
+            many1(map(alt((char('L'), char('R'))), Instr::from)),
             many1(line_ending),
         )(input);
 
@@ -77,7 +79,7 @@ impl InputParser {
             panic!();
         };
 
-        assert!(left.len() == 0);
+        assert!(left.is_empty());
 
         (VecDeque::from(instr), docs)
     }
@@ -88,7 +90,6 @@ pub fn part_one(input: &str) -> Option<u32> {
     let mut doc_maps = HashMap::new();
     docs.iter().for_each(|d| {
         doc_maps.insert(d.name, d);
-        ()
     });
 
     // let instr_count = instr.len();
@@ -108,34 +109,67 @@ pub fn part_two(input: &str) -> Option<usize> {
     let mut doc_maps = HashMap::new();
     docs.iter().for_each(|d| {
         doc_maps.insert(d.name, d);
-        ()
     });
 
-    let instr_len = instr.len();
+    let step = |&(name, i): &(&str, usize)| {
+        let doc = doc_maps.get(name).unwrap();
+        (instr[i].apply(doc), (i + 1) % instr.len())
+    };
 
-    // get periods
-    let periods: Vec<usize> = docs
+    // every ghost's cycle, with the moves after which it is on a Z node until the cycle closes.
+    let ghosts: Vec<(Cycle, Vec<usize>)> = docs
         .iter()
         .par_bridge()
         .filter(|&d| d.name.ends_with('A'))
-        .map(|s| {
-            let mut s = s;
-            let mut move_count = 0;
-            while !s.name.ends_with('Z') || move_count % instr_len != 0 {
-                s = doc_maps
-                    .get(instr[move_count % instr_len].apply(s))
-                    .unwrap();
-                move_count += 1;
+        .map(|d| {
+            let start = (d.name, 0);
+            let cycle = cycle::find_brent(start, step);
+            let mut state = start;
+            let mut arrivals = Vec::new();
+            for move_count in 0..cycle.prefix + cycle.period {
+                if state.0.ends_with('Z') {
+                    arrivals.push(move_count);
+                }
+                state = step(&state);
             }
-            move_count / instr_len
+            (cycle, arrivals)
         })
         .collect();
-    let lcm = periods
-        .into_iter()
-        .par_bridge()
-        .reduce(|| 1, |acc, el| acc.lcm(&el));
 
-    Some(lcm * instr_len)
+    // before all the ghosts are in their cycles, just check every move.
+    let max_prefix = ghosts.iter().map(|(c, _)| c.prefix).max()?;
+    let on_z = |move_count: usize| {
+        ghosts
+            .iter()
+            .all(|(c, arrivals)| arrivals.binary_search(&c.reduce(move_count)).is_ok())
+    };
+    if let Some(move_count) = (1..max_prefix).find(|&m| on_z(m)) {
+        return Some(move_count);
+    }
+
+    // then every ghost is on a Z node at one of its arrivals modulo its period.
+    ghosts
+        .iter()
+        .map(|(c, arrivals)| {
+            arrivals
+                .iter()
+                .filter(|&&a| a >= c.prefix)
+                .map(|&a| (a as i128, c.period as i128))
+                .collect::<Vec<_>>()
+        })
+        .multi_cartesian_product()
+        .filter_map(math::crt)
+        .map(|(x, m)| {
+            // the smallest solution once all the ghosts are in their cycles.
+            let lowest = (max_prefix as i128).max(1);
+            if x >= lowest {
+                x
+            } else {
+                x + (lowest - x + m - 1) / m * m
+            }
+        })
+        .min()
+        .and_then(|move_count| move_count.try_into().ok())
 }
 
 #[cfg(test)]
@@ -150,6 +184,21 @@ mod tests {
         assert_eq!(result, Some(6));
     }
 
+    #[test]
+    fn test_part_two_unaligned() {
+        // Z nodes after 2 + 3k moves for the first ghost, 1 + 4k moves for the second one.
+        let input = "L\n\n\
+            11A = (11B, 11B)\n11B = (11Z, 11Z)\n11Z = (11C, 11C)\n11C = (11B, 11B)\n\
+            22A = (22Z, 22Z)\n22Z = (22B, 22B)\n22B = (22C, 22C)\n22C = (22D, 22D)\n\
+            22D = (22Z, 22Z)\n";
+        assert_eq!(part_two(input), Some(5));
+
+        // Z nodes after odd moves for the first ghost, even moves for the second one.
+        let input = "L\n\n11A = (11Z, 11Z)\n11Z = (11A, 11A)\n\
+            22A = (22B, 22B)\n22B = (22Z, 22Z)\n22Z = (22B, 22B)\n";
+        assert_eq!(part_two(input), None);
+    }
+
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file_part(
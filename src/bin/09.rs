@@ -6,11 +6,7 @@ impl InputParser {
     fn parse(input: &str) -> Vec<Vec<isize>> {
         input
             .lines()
-            .map(|l| {
-                l.split(' ')
-                    .map(|n| isize::from_str_radix(n, 10).unwrap())
-                    .collect()
-            })
+            .map(|l| l.split(' ').map(|n| n.parse::<isize>().unwrap()).collect())
             .collect()
     }
 }
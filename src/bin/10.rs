@@ -3,10 +3,14 @@ use std::{
     io::{Error, ErrorKind},
 };
 
+use advent_of_code::point::{Direction, Point};
+use advent_of_code::render::{self, Image, Rgb};
+use advent_of_code::search;
+use advent_of_code::template::Options;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-advent_of_code::solution!(10);
+advent_of_code::solution!(10, extras);
 
 #[derive(Clone, Copy, PartialEq, Eq, EnumIter)]
 enum Pipe {
@@ -49,6 +53,12 @@ impl TryFrom<char> for Pipe {
 //     }
 // }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Enclosed {
+    In,
+    Out,
+}
+
 struct PipeMaze {
     start_pos: (usize, usize),
     width: usize,
@@ -57,33 +67,22 @@ struct PipeMaze {
 }
 
 impl Pipe {
-    fn get_deltas(&self) -> &'static [(isize, isize)] {
+    fn get_deltas(&self) -> &'static [Direction] {
+        use Direction::{Down, Left, Right, Up};
         match &self {
-            Pipe::Start => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
-            Pipe::Vertical => &[(0, 1), (0, -1)],
-            Pipe::Horizontal => &[(1, 0), (-1, 0)],
-            Pipe::NorthToEast => &[(0, -1), (1, 0)],
-            Pipe::EastToSouth => &[(1, 0), (0, 1)],
-            Pipe::SouthToWest => &[(0, 1), (-1, 0)],
-            Pipe::WestToNorth => &[(-1, 0), (0, -1)],
+            Pipe::Start => &[Left, Right, Up, Down],
+            Pipe::Vertical => &[Down, Up],
+            Pipe::Horizontal => &[Right, Left],
+            Pipe::NorthToEast => &[Up, Right],
+            Pipe::EastToSouth => &[Right, Down],
+            Pipe::SouthToWest => &[Down, Left],
+            Pipe::WestToNorth => &[Left, Up],
         }
     }
 }
 
-trait ApplyDelta<T>: Sized {
-    fn try_delta(self, delta: T) -> Option<Self>;
-}
-
-impl ApplyDelta<(isize, isize)> for (usize, usize) {
-    fn try_delta(self, delta: (isize, isize)) -> Option<Self> {
-        let x = (self.0 as isize) + delta.0;
-        let y = (self.1 as isize) + delta.1;
-        if x < 0 || y < 0 {
-            None
-        } else {
-            Some((x as usize, y as usize))
-        }
-    }
+fn try_step(pos: (usize, usize), dir: Direction) -> Option<(usize, usize)> {
+    Point::from(pos).checked_step(dir).map(Into::into)
 }
 
 impl PipeMaze {
@@ -137,73 +136,39 @@ impl PipeMaze {
         empty_map
     }
 
+    /// Neighbours connected to the pipe at `pos`, that is whose pipe leads back to it.
+    fn connections(
+        &self,
+        pos: (usize, usize),
+    ) -> impl Iterator<Item = (Direction, (usize, usize))> + '_ {
+        let deltas = self.get_pipe(pos).map_or(&[][..], |pipe| pipe.get_deltas());
+        deltas.iter().filter_map(move |&delta| {
+            let new_pos = try_step(pos, delta)?;
+            self.get_pipe(new_pos)?
+                .get_deltas()
+                .contains(&delta.opposite())
+                .then_some((delta, new_pos))
+        })
+    }
+
     fn get_distance_map(&self) -> Vec<Vec<Option<usize>>> {
         let mut distance_map = self.get_empty_map::<usize>();
-        let mut positions = VecDeque::new();
-
-        distance_map[self.start_pos.1][self.start_pos.0] = Some(0);
-        for &delta_around_start in self.get_pipe(self.start_pos).unwrap().get_deltas() {
-            if let Some(new_pos) = self.start_pos.try_delta(delta_around_start) {
-                if let Some(new_pos_pipe) = self.get_pipe(new_pos) {
-                    let minus_delta_around_start = (-delta_around_start.0, -delta_around_start.1);
-                    if new_pos_pipe
-                        .get_deltas()
-                        .contains(&minus_delta_around_start)
-                    {
-                        // this pipe really connects to start.
-                        positions.push_back((new_pos, 1));
-                    }
-                }
-            }
-        }
-
-        while let Some((pos, dist)) = positions.pop_front() {
-            match distance_map.get_mut(pos.1).unwrap().get_mut(pos.0).unwrap() {
-                v if v == &mut None => {
-                    *v = Some(dist);
-                }
-                Some(previous_dist) => {
-                    if *previous_dist < dist {
-                        continue;
-                    } else {
-                        *previous_dist = dist
-                    }
-                }
-                _ => unreachable!(),
-            }
-            let deltas = {
-                if let Some(pipe) = self.get_pipe(pos) {
-                    pipe.get_deltas()
-                } else {
-                    &[]
-                }
-            };
-            for &delta in deltas {
-                if let Some(new_pos) = pos.try_delta(delta) {
-                    positions.push_back((new_pos, dist + 1))
-                }
-            }
+        let visited = search::bfs(
+            [self.start_pos],
+            |&pos| self.connections(pos).map(|(_, new_pos)| new_pos),
+            |_| false,
+        );
+        for (&(x, y), dist) in visited.iter() {
+            distance_map[y][x] = Some(dist);
         }
         distance_map
     }
 
     fn deduce_start(&self) -> Pipe {
-        let mut valid_deltas = Vec::new();
-
-        for &delta_around_start in self.get_pipe(self.start_pos).unwrap().get_deltas() {
-            if let Some(new_pos) = self.start_pos.try_delta(delta_around_start) {
-                if let Some(new_pos_pipe) = self.get_pipe(new_pos) {
-                    let minus_delta_around_start = (-delta_around_start.0, -delta_around_start.1);
-                    if new_pos_pipe
-                        .get_deltas()
-                        .contains(&minus_delta_around_start)
-                    {
-                        // this pipe really connects to start.
-                        valid_deltas.push(delta_around_start);
-                    }
-                }
-            }
-        }
+        let valid_deltas: Vec<Direction> = self
+            .connections(self.start_pos)
+            .map(|(delta, _)| delta)
+            .collect();
         'enum_loop: for pipe in Pipe::iter() {
             if pipe == Pipe::Start {
                 continue;
@@ -222,17 +187,44 @@ impl PipeMaze {
         self.get_distance_map()
             .into_iter()
             .flatten()
-            .filter_map(|x| x)
+            .flatten()
             .reduce(|vacc, vel| vacc.max(vel))
     }
 
     fn get_enclosed(&self) -> Option<usize> {
-        #[derive(Clone, Copy, PartialEq, Eq)]
-        enum Enclosed {
-            In,
-            Out,
-        }
         let loop_map = self.get_distance_map();
+        Some(
+            self.get_enclosed_map(&loop_map)
+                .into_iter()
+                .flatten()
+                .filter(|&e| e == Some(Enclosed::In))
+                .count(),
+        )
+    }
+
+    /// Tiles of the loop in a gradient of their distance to the start, enclosed tiles in green.
+    fn render(&self, scale: usize) -> Image {
+        let loop_map = self.get_distance_map();
+        let enclosed_map = self.get_enclosed_map(&loop_map);
+        let furthest = loop_map
+            .iter()
+            .flatten()
+            .flatten()
+            .max()
+            .copied()
+            .unwrap_or(0);
+        Image::from_fn(self.width, self.height, scale, |(x, y)| {
+            match (loop_map[y][x], enclosed_map[y][x]) {
+                (Some(dist), _) => Rgb::new(255, 220, 0)
+                    .lerp(Rgb::new(220, 20, 60), dist as f64 / furthest.max(1) as f64),
+                (None, Some(Enclosed::In)) => Rgb::new(40, 170, 70),
+                (None, _) => Rgb::gray(30),
+            }
+        })
+    }
+
+    /// Whether each tile off the loop is enclosed by it, [`None`] for the loop itself.
+    fn get_enclosed_map(&self, loop_map: &[Vec<Option<usize>>]) -> Vec<Vec<Option<Enclosed>>> {
         let mut enclosed_map = self.get_empty_map::<Enclosed>();
 
         for y in 0..self.height {
@@ -267,7 +259,7 @@ impl PipeMaze {
                     region[pos.1][pos.0] = Some(());
 
                     for &delta in Pipe::Start.get_deltas() {
-                        if let Some(new_pos) = pos.try_delta(delta) {
+                        if let Some(new_pos) = try_step(pos, delta) {
                             around.push_back(new_pos);
                         }
                     }
@@ -289,8 +281,8 @@ impl PipeMaze {
                         .unwrap();
                     let mut vertical_layers_count = 0;
                     let mut expected_pipe = None;
-                    for dx in 0..px {
-                        if loop_map[py][dx].is_none() {
+                    for (dx, loop_tile) in loop_map[py].iter().enumerate().take(px) {
+                        if loop_tile.is_none() {
                             continue;
                         }
                         if let Some(p) = self.get_pipe((dx, py)) {
@@ -376,13 +368,7 @@ impl PipeMaze {
             }
         }
 
-        Some(
-            enclosed_map
-                .into_iter()
-                .flatten()
-                .filter(|&e| e == Some(Enclosed::In))
-                .count(),
-        )
+        enclosed_map
     }
 }
 
@@ -396,6 +382,11 @@ pub fn part_two(input: &str) -> Option<usize> {
     pipe_maze.get_enclosed()
 }
 
+/// Renders the maze with `--render`.
+fn extras(input: &str, options: &Options) {
+    render::render_requested(options, |scale| PipeMaze::new(input).render(scale));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
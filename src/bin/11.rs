@@ -11,10 +11,7 @@ enum Observable {
 
 impl Observable {
     fn is_void(&self) -> bool {
-        match &self {
-            Observable::Void(_) => true,
-            _ => false,
-        }
+        matches!(self, Observable::Void(_))
     }
 
     fn set(&mut self, n: usize) {
@@ -106,6 +103,7 @@ impl Universe {
         GalaxyIterator {
             universe: self,
             current_pos: (0, 0),
+            started: false,
             universe_dims: (self.univ[0].len(), self.univ.len()),
         }
     }
@@ -132,12 +130,20 @@ impl Universe {
 struct GalaxyIterator<'a> {
     universe: &'a Universe,
     current_pos: (usize, usize),
+    started: bool,
     universe_dims: (usize, usize),
 }
 
 impl<'a> Iterator for GalaxyIterator<'a> {
     type Item = (usize, usize);
     fn next(&mut self) -> Option<Self::Item> {
+        // the first position is not skipped, it may be a galaxy too.
+        if !self.started {
+            self.started = true;
+            if self.universe.univ[0][0] == Observable::Galaxy {
+                return Some(self.current_pos);
+            }
+        }
         if self.current_pos.0 + 1 >= self.universe_dims.0 {
             self.current_pos.1 += 1;
             self.current_pos.0 = 0;
@@ -197,6 +203,11 @@ pub fn part_two(input: &str) -> Option<usize> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_galaxy_at_origin() {
+        assert_eq!(part_one("#.\n.#\n"), Some(2));
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
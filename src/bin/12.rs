@@ -1,6 +1,6 @@
-use std::collections::HashMap;
-
-use rayon::iter::ParallelBridge;
+use advent_of_code::memo::MemoTable;
+use advent_of_code::parse::{OrReport, ParseError, Source};
+use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
 
 advent_of_code::solution!(12);
@@ -27,13 +27,15 @@ enum Spring {
 //     println!("");
 // }
 
-impl From<char> for Spring {
-    fn from(value: char) -> Self {
+impl TryFrom<char> for Spring {
+    type Error = char;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
-            '.' => Self::Operational,
-            '#' => Self::Damaged,
-            '?' => Self::Unknown,
-            _ => unreachable!(),
+            '.' => Ok(Self::Operational),
+            '#' => Ok(Self::Damaged),
+            '?' => Ok(Self::Unknown),
+            c => Err(c),
         }
     }
 }
@@ -43,19 +45,31 @@ struct Record {
     groups: Vec<usize>,
 }
 
-impl From<&str> for Record {
-    fn from(value: &str) -> Self {
-        let (springs, groups) = value.split_once(' ').unwrap();
-        Record {
-            springs: springs.chars().map(Spring::from).collect(),
-            groups: groups
-                .split(',')
-                .map(|n| usize::from_str_radix(n, 10).unwrap())
-                .collect(),
-        }
+impl Record {
+    fn parse<'a>(source: &Source<'a>, line: &'a str) -> Result<Self, ParseError> {
+        let (springs, groups) = source.split_once(line, " ")?;
+        Ok(Record {
+            springs: springs
+                .char_indices()
+                .map(|(i, c)| {
+                    Spring::try_from(c).map_err(|c| {
+                        source.error(&springs[i..i + c.len_utf8()], format!("unexpected {c:?}"))
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            groups: source.numbers(groups)?,
+        })
     }
 }
 
+fn parse_records(input: &str) -> Result<Vec<Record>, ParseError> {
+    let source = Source::new(input);
+    source
+        .lines()
+        .map(|line| Record::parse(&source, line))
+        .collect()
+}
+
 impl Record {
     fn unfold(&mut self) {
         let base_springs = self.springs.clone();
@@ -71,20 +85,34 @@ impl Record {
     }
 
     fn _count_arrangements(
+        &self,
+        group_to_match_index: usize,
+        currently_matching: Option<usize>,
+        spring_index: usize,
+        memo: &mut MemoTable<Result<usize, ()>, 3>,
+    ) -> Result<usize, ()> {
+        let key = [
+            group_to_match_index,
+            currently_matching.map_or(0, |curr| curr + 1),
+            spring_index,
+        ];
+        memo.get_or_compute(key, |memo| {
+            self.count_from(group_to_match_index, currently_matching, spring_index, memo)
+        })
+    }
+
+    /// The uncached body of [`Record::_count_arrangements`].
+    fn count_from(
         &self,
         mut group_to_match_index: usize,
         mut currently_matching: Option<usize>,
         spring_index: usize,
-        cache: &mut HashMap<(usize, Option<usize>, usize), Result<usize, ()>>,
+        memo: &mut MemoTable<Result<usize, ()>, 3>,
     ) -> Result<usize, ()> {
-        if let Some(&entry) = cache.get(&(group_to_match_index, currently_matching, spring_index)) {
-            return entry;
-        }
-
         // check if this is worth it to keep parsing
         let total_damaged_to_find = self.groups[group_to_match_index..]
-            .to_owned()
-            .into_iter()
+            .iter()
+            .copied()
             .reduce(|acc, el| acc + el)
             .unwrap();
         let potential_damaged_springs_to_parse = self.springs[spring_index..]
@@ -171,34 +199,24 @@ impl Record {
                                 (Some(1), current_spring_index + 1)
                             };
                             // start damaged group
-                            let count1 = self._count_arrangements(
-                                group_to_match_index,
-                                new_currently_matching,
-                                new_spring_index,
-                                cache,
-                            );
-                            cache.insert(
-                                (
+                            let count1 = self
+                                ._count_arrangements(
                                     group_to_match_index,
                                     new_currently_matching,
                                     new_spring_index,
-                                ),
-                                count1,
-                            );
-                            let count1 = count1.unwrap_or(0);
+                                    memo,
+                                )
+                                .unwrap_or(0);
 
                             // start new_group later
-                            let count2 = self._count_arrangements(
-                                group_to_match_index,
-                                Some(0),
-                                current_spring_index + 1,
-                                cache,
-                            );
-                            cache.insert(
-                                (group_to_match_index, Some(0), current_spring_index + 1),
-                                count2,
-                            );
-                            let count2 = count2.unwrap_or(0);
+                            let count2 = self
+                                ._count_arrangements(
+                                    group_to_match_index,
+                                    Some(0),
+                                    current_spring_index + 1,
+                                    memo,
+                                )
+                                .unwrap_or(0);
 
                             return Ok(count1 + count2);
                         }
@@ -243,28 +261,31 @@ impl Record {
     }
 
     fn count_arrangements(&self) -> usize {
-        let mut cache = HashMap::new();
-        self._count_arrangements(0, Some(0), 0, &mut cache).unwrap()
+        let longest_group = self.groups.iter().copied().max().unwrap_or(0);
+        let mut memo = MemoTable::new([
+            self.groups.len() + 1,
+            longest_group + 2,
+            self.springs.len() + 1,
+        ]);
+        self._count_arrangements(0, Some(0), 0, &mut memo).unwrap()
     }
 }
 
 pub fn part_one(input: &str) -> Option<usize> {
+    let records = parse_records(input).or_report()?;
     Some(
-        input
-            .lines()
-            .par_bridge()
-            .map(Record::from)
+        records
+            .into_par_iter()
             .map(|r| r.count_arrangements())
             .reduce(|| 0, |acc, el| acc + el),
     )
 }
 
 pub fn part_two(input: &str) -> Option<usize> {
+    let records = parse_records(input).or_report()?;
     Some(
-        input
-            .lines()
-            .par_bridge()
-            .map(Record::from)
+        records
+            .into_par_iter()
             .map(|mut r| {
                 r.unfold();
                 r
@@ -276,8 +297,103 @@ pub fn part_two(input: &str) -> Option<usize> {
 
 #[cfg(test)]
 mod tests {
+    use std::fmt::Display;
+
+    use advent_of_code::generate::shapes::{record_line, record_springs};
+    use advent_of_code::property::{self, shrink_vec, Arbitrary};
+    use advent_of_code::random::Rng;
+
     use super::*;
 
+    /// A record generated from a known arrangement, so that it always has at least one solution.
+    #[derive(Clone)]
+    struct RecordCase {
+        /// `(damaged, unknown)` for every spring.
+        springs: Vec<(bool, bool)>,
+    }
+
+    impl RecordCase {
+        fn unknown_count(&self) -> usize {
+            self.springs.iter().filter(|s| s.1).count()
+        }
+    }
+
+    impl Display for RecordCase {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            writeln!(f, "{}", record_line(&self.springs))
+        }
+    }
+
+    impl Arbitrary for RecordCase {
+        fn arbitrary(rng: &mut Rng, size: usize) -> Self {
+            let len = rng.range_usize(1, size + 2);
+            RecordCase {
+                springs: record_springs(rng, len),
+            }
+        }
+
+        fn shrink(&self) -> Vec<Self> {
+            shrink_vec(&self.springs, 1, |&(damaged, unknown)| {
+                match (damaged, unknown) {
+                    (_, true) => vec![(damaged, false)],
+                    (true, false) => vec![(false, false)],
+                    (false, false) => vec![],
+                }
+            })
+            .into_iter()
+            .map(|springs| RecordCase { springs })
+            .filter(|case| case.springs.iter().any(|s| s.0))
+            .collect()
+        }
+    }
+
+    /// Tries every assignment of the unknown springs.
+    fn brute_force_arrangements(springs: &[Spring], groups: &[usize]) -> usize {
+        let unknowns: Vec<usize> = (0..springs.len())
+            .filter(|&i| springs[i] == Spring::Unknown)
+            .collect();
+        (0..1_usize << unknowns.len())
+            .filter(|mask| {
+                let mut damaged: Vec<bool> =
+                    springs.iter().map(|s| *s == Spring::Damaged).collect();
+                for (bit, &i) in unknowns.iter().enumerate() {
+                    damaged[i] = mask & (1 << bit) != 0;
+                }
+                let found: Vec<usize> = damaged
+                    .split(|&d| !d)
+                    .map(<[_]>::len)
+                    .filter(|&len| len > 0)
+                    .collect();
+                found == groups
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        property::check(|case: &RecordCase| {
+            let input = case.to_string();
+            let record = parse_records(&input).unwrap().remove(0);
+            let expected = brute_force_arrangements(&record.springs, &record.groups);
+            let result = part_one(&input);
+            if result != Some(expected) {
+                return Err(format!("part one: expected {expected}, got {result:?}"));
+            }
+
+            // unfolding multiplies the unknowns by five, keep the brute force cheap.
+            if case.unknown_count() <= 2 {
+                let mut unfolded = parse_records(&input).unwrap().remove(0);
+                unfolded.unfold();
+                let expected = brute_force_arrangements(&unfolded.springs, &unfolded.groups);
+                let result = part_two(&input);
+                if result != Some(expected) {
+                    return Err(format!("part two: expected {expected}, got {result:?}"));
+                }
+            }
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
@@ -1,3 +1,5 @@
+use advent_of_code::grid::Grid;
+
 advent_of_code::solution!(13);
 
 enum Element {
@@ -25,20 +27,26 @@ impl From<&Element> for usize {
 }
 
 struct Pattern {
-    map: Vec<Vec<Element>>,
+    map: Grid<Element>,
 }
 
 impl From<&str> for Pattern {
     fn from(value: &str) -> Self {
         Self {
-            map: value
-                .lines()
-                .map(|l| l.chars().map(Element::from).collect())
-                .collect(),
+            map: Grid::parse(value, Element::from).unwrap(),
         }
     }
 }
 
+/// Packs a line or column of the pattern in the bits of a number.
+fn as_bits<'a>(elements: impl Iterator<Item = &'a Element>) -> usize {
+    elements
+        .map(usize::from)
+        .enumerate()
+        .map(|(i, n)| n << i)
+        .sum()
+}
+
 enum ReflectResult {
     Line(usize),
     Column(usize),
@@ -55,28 +63,8 @@ impl ReflectResult {
 
 impl Pattern {
     fn get_reflection(&self, need_smudge: bool) -> ReflectResult {
-        let lines: Vec<usize> = self
-            .map
-            .iter()
-            .map(|e| {
-                e.iter()
-                    .map(usize::from)
-                    .enumerate()
-                    .map(|(i, n)| n << i)
-                    .sum()
-            })
-            .collect();
-
-        let col_count = self.map[0].len();
-        let columns: Vec<usize> = (0..col_count)
-            .map(|col| {
-                self.map
-                    .iter()
-                    .enumerate()
-                    .map(|(i, l)| usize::from(l.iter().nth(col).unwrap()) << i)
-                    .sum()
-            })
-            .collect();
+        let lines: Vec<usize> = self.map.rows().map(|r| as_bits(r.iter())).collect();
+        let columns: Vec<usize> = self.map.columns().map(as_bits).collect();
         for (nums, result_fn) in [
             (lines, ReflectResult::line as fn(usize) -> ReflectResult),
             (columns, ReflectResult::column as fn(usize) -> ReflectResult),
@@ -152,8 +140,140 @@ pub fn part_two(input: &str) -> Option<usize> {
 
 #[cfg(test)]
 mod tests {
+    use std::fmt::Display;
+
+    use advent_of_code::property::{self, Arbitrary};
+    use advent_of_code::random::Rng;
+
     use super::*;
 
+    /// A pattern reflected around a random line, with an optional smudge (one flipped cell).
+    #[derive(Clone)]
+    struct PatternCase {
+        rocks: Vec<Vec<bool>>,
+    }
+
+    impl Display for PatternCase {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            for row in &self.rocks {
+                let row: String = row.iter().map(|&r| if r { '#' } else { '.' }).collect();
+                writeln!(f, "{row}")?;
+            }
+            Ok(())
+        }
+    }
+
+    impl PatternCase {
+        fn transposed(&self) -> Vec<Vec<bool>> {
+            (0..self.rocks[0].len())
+                .map(|x| self.rocks.iter().map(|row| row[x]).collect())
+                .collect()
+        }
+
+        /// All reflections with exactly `smudges` differences, as `(is_line, index)`.
+        fn brute_force_reflections(&self, smudges: usize) -> Vec<(bool, usize)> {
+            let reflections = |rows: &Vec<Vec<bool>>| -> Vec<usize> {
+                (1..rows.len())
+                    .filter(|&i| {
+                        let differences: usize = (0..i.min(rows.len() - i))
+                            .map(|d| {
+                                rows[i - 1 - d]
+                                    .iter()
+                                    .zip(&rows[i + d])
+                                    .filter(|(a, b)| a != b)
+                                    .count()
+                            })
+                            .sum();
+                        differences == smudges
+                    })
+                    .collect()
+            };
+            let mut found: Vec<(bool, usize)> = reflections(&self.rocks)
+                .into_iter()
+                .map(|i| (true, i))
+                .collect();
+            found.extend(
+                reflections(&self.transposed())
+                    .into_iter()
+                    .map(|i| (false, i)),
+            );
+            found
+        }
+    }
+
+    impl Arbitrary for PatternCase {
+        fn arbitrary(rng: &mut Rng, size: usize) -> Self {
+            let width = rng.range_usize(2, size + 2);
+            let height = rng.range_usize(2, size + 2);
+            let mut rocks: Vec<Vec<bool>> = (0..height)
+                .map(|_| (0..width).map(|_| rng.bool()).collect())
+                .collect();
+
+            // mirror the rows around a random line, then transpose back if needed.
+            let columns = rng.bool();
+            if columns {
+                rocks = PatternCase { rocks }.transposed();
+            }
+            let line = rng.range_usize(1, rocks.len() - 1);
+            for d in 0..line.min(rocks.len() - line) {
+                rocks[line + d] = rocks[line - 1 - d].clone();
+            }
+            if rng.bool() {
+                let y = rng.range_usize(0, rocks.len() - 1);
+                let x = rng.range_usize(0, rocks[0].len() - 1);
+                rocks[y][x] = !rocks[y][x];
+            }
+            if columns {
+                rocks = PatternCase { rocks }.transposed();
+            }
+            PatternCase { rocks }
+        }
+
+        fn shrink(&self) -> Vec<Self> {
+            let mut candidates = Vec::new();
+            if self.rocks.len() > 2 {
+                candidates.push(self.rocks[1..].to_vec());
+                candidates.push(self.rocks[..self.rocks.len() - 1].to_vec());
+            }
+            if self.rocks[0].len() > 2 {
+                candidates.push(self.rocks.iter().map(|row| row[1..].to_vec()).collect());
+                candidates.push(
+                    self.rocks
+                        .iter()
+                        .map(|row| row[..row.len() - 1].to_vec())
+                        .collect(),
+                );
+            }
+            candidates
+                .into_iter()
+                .map(|rocks| PatternCase { rocks })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        property::check(|case: &PatternCase| {
+            let pattern = Pattern::from(case.to_string().as_str());
+            // the puzzle guarantees a single reflection, other cases are not valid inputs.
+            for (smudges, need_smudge) in [(0, false), (1, true)] {
+                let [expected] = case.brute_force_reflections(smudges)[..] else {
+                    continue;
+                };
+                let result = match pattern.get_reflection(need_smudge) {
+                    ReflectResult::Line(n) => (true, n),
+                    ReflectResult::Column(n) => (false, n),
+                };
+                if result != expected {
+                    return Err(format!(
+                        "{smudges} smudge(s): expected {expected:?}, got {result:?}"
+                    ));
+                }
+            }
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
@@ -1,10 +1,12 @@
-use std::{
-    collections::HashMap,
-    fmt::{Debug, Write},
-    hash::Hash,
-};
+use std::fmt::{Debug, Write};
 
-advent_of_code::solution!(14);
+use advent_of_code::cycle;
+use advent_of_code::grid::Grid;
+use advent_of_code::record::{Cell, Frame, Recorder, Recording};
+use advent_of_code::render::{self, Image, Rgb};
+use advent_of_code::template::Options;
+
+advent_of_code::solution!(14, extras);
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy)]
 enum Element {
@@ -23,38 +25,33 @@ impl TryFrom<char> for Element {
     }
 }
 
+#[derive(Clone, PartialEq, Eq, Hash)]
 struct Board {
-    board: Vec<Vec<Option<Element>>>,
+    board: Grid<Option<Element>>,
 }
 
 impl From<&str> for Board {
     fn from(value: &str) -> Self {
         Board {
-            board: value
-                .lines()
-                .map(|line| line.chars().map(|c| Element::try_from(c).ok()).collect())
-                .collect(),
+            board: Grid::parse(value, |c| Element::try_from(c).ok()).unwrap(),
         }
     }
 }
 
 impl Board {
     fn north_load(&self) -> usize {
-        let line_count = self.board.len();
+        let line_count = self.board.height();
         let mut load = 0;
-        for col in 0..self.board[0].len() {
+        for col in 0..self.board.width() {
             for line in 0..line_count {
-                match self.board[line][col] {
-                    Some(Element::Rock) => {
-                        load += line_count - line;
-                    }
-                    _ => (),
+                if let Some(Element::Rock) = self.board[(col, line)] {
+                    load += line_count - line;
                 }
             }
         }
         load
-        // let column_count = self.board[0].len();
-        // let line_count = self.board.len();
+        // let column_count = self.board.width();
+        // let line_count = self.board.height();
 
         // let mut last_free_space;
         // let mut load = 0;
@@ -62,7 +59,7 @@ impl Board {
         // for col in 0..column_count {
         //     last_free_space = 0;
         //     for line in 0..line_count {
-        //         match self.board[line][col] {
+        //         match self.board[(col, line)] {
         //             None => (),
         //             Some(Element::Rock) => {
         //                 load += line_count - last_free_space;
@@ -78,20 +75,20 @@ impl Board {
     }
 
     fn north_tilt(&mut self) {
-        let column_count = self.board[0].len();
-        let line_count = self.board.len();
+        let column_count = self.board.width();
+        let line_count = self.board.height();
 
         let mut last_free_line;
 
         for col in 0..column_count {
             last_free_line = 0;
             for line in 0..line_count {
-                match self.board[line][col] {
+                match self.board[(col, line)] {
                     None => (),
                     Some(Element::Rock) => {
                         if line != last_free_line {
-                            self.board[line][col] = None;
-                            self.board[last_free_line][col] = Some(Element::Rock);
+                            self.board[(col, line)] = None;
+                            self.board[(col, last_free_line)] = Some(Element::Rock);
                         }
                         last_free_line += 1;
                     }
@@ -103,20 +100,20 @@ impl Board {
         }
     }
     fn west_tilt(&mut self) {
-        let column_count = self.board[0].len();
-        let line_count = self.board.len();
+        let column_count = self.board.width();
+        let line_count = self.board.height();
 
         let mut last_free_col;
 
         for line in 0..line_count {
             last_free_col = 0;
             for col in 0..column_count {
-                match self.board[line][col] {
+                match self.board[(col, line)] {
                     None => (),
                     Some(Element::Rock) => {
                         if col != last_free_col {
-                            self.board[line][col] = None;
-                            self.board[line][last_free_col] = Some(Element::Rock);
+                            self.board[(col, line)] = None;
+                            self.board[(last_free_col, line)] = Some(Element::Rock);
                         }
                         last_free_col += 1;
                     }
@@ -128,20 +125,20 @@ impl Board {
         }
     }
     fn south_tilt(&mut self) {
-        let column_count = self.board[0].len();
-        let line_count = self.board.len();
+        let column_count = self.board.width();
+        let line_count = self.board.height();
 
         let mut last_free_line;
 
         for col in 0..column_count {
             last_free_line = line_count - 1;
             for line in (0..line_count).rev() {
-                match self.board[line][col] {
+                match self.board[(col, line)] {
                     None => (),
                     Some(Element::Rock) => {
                         if line != last_free_line {
-                            self.board[line][col] = None;
-                            self.board[last_free_line][col] = Some(Element::Rock);
+                            self.board[(col, line)] = None;
+                            self.board[(col, last_free_line)] = Some(Element::Rock);
                         }
                         last_free_line = last_free_line.saturating_sub(1);
                     }
@@ -153,20 +150,20 @@ impl Board {
         }
     }
     fn east_tilt(&mut self) {
-        let column_count = self.board[0].len();
-        let line_count = self.board.len();
+        let column_count = self.board.width();
+        let line_count = self.board.height();
 
         let mut last_free_col;
 
         for line in 0..line_count {
             last_free_col = column_count - 1;
             for col in (0..column_count).rev() {
-                match self.board[line][col] {
+                match self.board[(col, line)] {
                     None => (),
                     Some(Element::Rock) => {
                         if col != last_free_col {
-                            self.board[line][col] = None;
-                            self.board[line][last_free_col] = Some(Element::Rock);
+                            self.board[(col, line)] = None;
+                            self.board[(last_free_col, line)] = Some(Element::Rock);
                         }
                         last_free_col = last_free_col.saturating_sub(1);
                     }
@@ -178,39 +175,50 @@ impl Board {
         }
     }
 
-    fn cycle(&mut self, n: usize) {
-        let mut cache = HashMap::new();
-        let mut cycle_realised = 0;
-        cache.insert(self.board.clone(), cycle_realised);
-        // println!("Start: \n{:?}", &self);
-        while cycle_realised < n {
-            self.north_tilt();
-            // println!("After north tilt: \n{:?}", &self);
-            self.west_tilt();
-            // println!("After west tilt: \n{:?}", &self);
-            self.south_tilt();
-            // println!("After south tilt: \n{:?}", &self);
-            self.east_tilt();
-            // println!("After east tilt: \n{:?}", &self);
-            cycle_realised += 1;
-            if let Some(old_board_cycle_count) = cache.get(&self.board) {
-                let period = cycle_realised - old_board_cycle_count;
-                if period < n - cycle_realised {
-                    let nearest = n - ((n - old_board_cycle_count) % period);
-                    // dbg!(cycle_realised, old_board_cycle_count, period, nearest);
-                    cycle_realised = nearest;
-                }
-            } else {
-                cache.insert(self.board.clone(), cycle_realised);
-            }
+    /// Tilts north, west, south then east, recording the board after each tilt.
+    fn spin(&mut self, recorder: &mut impl Recorder) {
+        for tilt in [
+            Self::north_tilt,
+            Self::west_tilt,
+            Self::south_tilt,
+            Self::east_tilt,
+        ] {
+            tilt(self);
+            recorder.frame(|| self.frame());
         }
-        assert_eq!(cycle_realised, n);
+    }
+
+    /// Spins `n` times, only the spins until the boards repeat are recorded.
+    fn cycle(&mut self, n: usize, recorder: &mut impl Recorder) {
+        recorder.frame(|| self.frame());
+        let jump = cycle::nth(self.clone(), n, |board| {
+            let mut board = board.clone();
+            board.spin(recorder);
+            board
+        });
+        *self = jump.state;
+    }
+
+    fn frame(&self) -> Frame {
+        Frame::from_grid(&self.board, |_, element| match element {
+            None => Cell::new('.', Rgb::gray(80)),
+            Some(Element::Rock) => Cell::new('O', Rgb::new(230, 160, 40)),
+            Some(Element::SquareRock) => Cell::new('#', Rgb::gray(170)),
+        })
+    }
+
+    fn render(&self, scale: usize) -> Image {
+        Image::from_grid(&self.board, scale, |_, element| match element {
+            None => Rgb::gray(30),
+            Some(Element::Rock) => Rgb::new(230, 160, 40),
+            Some(Element::SquareRock) => Rgb::gray(120),
+        })
     }
 }
 
 impl Debug for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.board.iter().for_each(|l| {
+        self.board.rows().for_each(|l| {
             l.iter().for_each(|&e| {
                 match e {
                     None => f.write_char('.'),
@@ -233,10 +241,24 @@ pub fn part_one(input: &str) -> Option<usize> {
 
 pub fn part_two(input: &str) -> Option<usize> {
     let mut board = Board::from(input);
-    board.cycle(1_000_000_000);
+    board.cycle(1_000_000_000, &mut ());
     Some(board.north_load())
 }
 
+/// Records the tilts of part two with `--record` or `--play`, and renders the board at its end
+/// with `--render`.
+fn extras(input: &str, options: &Options) {
+    if let Some(mut recording) = Recording::from_options(options) {
+        Board::from(input).cycle(1_000_000_000, &mut recording);
+        recording.finish();
+    }
+    render::render_requested(options, |scale| {
+        let mut board = Board::from(input);
+        board.cycle(1_000_000_000, &mut ());
+        board.render(scale)
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,4 +1,10 @@
-advent_of_code::solution!(16);
+use advent_of_code::grid::Grid;
+use advent_of_code::point::{Axis, Direction, Point};
+use advent_of_code::record::{Cell, Frame, Recorder, Recording};
+use advent_of_code::render::{self, Image, Rgb};
+use advent_of_code::template::Options;
+
+advent_of_code::solution!(16, extras);
 
 #[derive(Clone, Copy)]
 enum MirrorMazeElement {
@@ -8,26 +14,6 @@ enum MirrorMazeElement {
     SplitterVertical,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl Direction {
-    fn apply_delta(&self, (x, y): (usize, usize)) -> Option<(usize, usize)> {
-        match self {
-            Direction::Up if y > 0 => Some((x, y - 1)),
-            Direction::Right => Some((x + 1, y)),
-            Direction::Down => Some((x, y + 1)),
-            Direction::Left if x > 0 => Some((x - 1, y)),
-            _ => None,
-        }
-    }
-}
-
 impl TryFrom<char> for MirrorMazeElement {
     type Error = ();
     fn try_from(value: char) -> Result<Self, Self::Error> {
@@ -42,64 +28,34 @@ impl TryFrom<char> for MirrorMazeElement {
 }
 
 impl MirrorMazeElement {
-    fn get_next_pos(
-        &self,
-        lx: usize,
-        ly: usize,
-        ldir: Direction,
-        mut cb: impl FnMut(usize, usize, Direction) -> (),
-    ) {
+    fn char(&self) -> char {
         match self {
-            MirrorMazeElement::SplitterHorizontal => match ldir {
-                Direction::Up | Direction::Down => {
-                    for new_dir in [Direction::Right, Direction::Left] {
-                        if let Some((new_lx, new_ly)) = new_dir.apply_delta((lx, ly)) {
-                            cb(new_lx, new_ly, new_dir);
-                        }
-                    }
-                }
-                Direction::Right | Direction::Left => {
-                    if let Some((new_lx, new_ly)) = ldir.apply_delta((lx, ly)) {
-                        cb(new_lx, new_ly, ldir)
-                    }
-                }
-            },
-            MirrorMazeElement::SplitterVertical => match ldir {
-                Direction::Right | Direction::Left => {
-                    for new_dir in [Direction::Up, Direction::Down] {
-                        if let Some((new_lx, new_ly)) = new_dir.apply_delta((lx, ly)) {
-                            cb(new_lx, new_ly, new_dir);
-                        }
-                    }
-                }
-                Direction::Up | Direction::Down => {
-                    if let Some((new_lx, new_ly)) = ldir.apply_delta((lx, ly)) {
-                        cb(new_lx, new_ly, ldir)
-                    }
-                }
-            },
-            MirrorMazeElement::MirrorSlash => {
-                let new_dir = match ldir {
-                    Direction::Up => Direction::Right,
-                    Direction::Right => Direction::Up,
-                    Direction::Down => Direction::Left,
-                    Direction::Left => Direction::Down,
-                };
-                if let Some((new_lx, new_ly)) = new_dir.apply_delta((lx, ly)) {
-                    cb(new_lx, new_ly, new_dir);
-                }
+            Self::SplitterHorizontal => '-',
+            Self::SplitterVertical => '|',
+            Self::MirrorSlash => '/',
+            Self::MirrorAntislash => '\\',
+        }
+    }
+
+    fn get_next_pos(&self, pos: Point, ldir: Direction, mut cb: impl FnMut(Point, Direction)) {
+        let mut go = |new_dir: Direction| {
+            if let Some(new_pos) = pos.checked_step(new_dir) {
+                cb(new_pos, new_dir);
             }
-            MirrorMazeElement::MirrorAntislash => {
-                let new_dir = match ldir {
-                    Direction::Up => Direction::Left,
-                    Direction::Right => Direction::Down,
-                    Direction::Down => Direction::Right,
-                    Direction::Left => Direction::Up,
-                };
-                if let Some((new_lx, new_ly)) = new_dir.apply_delta((lx, ly)) {
-                    cb(new_lx, new_ly, new_dir);
-                }
+        };
+        match (self, ldir.axis()) {
+            (MirrorMazeElement::SplitterHorizontal, Axis::Vertical)
+            | (MirrorMazeElement::SplitterVertical, Axis::Horizontal) => {
+                go(ldir.turn_right());
+                go(ldir.turn_left());
+            }
+            (MirrorMazeElement::SplitterHorizontal | MirrorMazeElement::SplitterVertical, _) => {
+                go(ldir)
             }
+            (MirrorMazeElement::MirrorSlash, Axis::Vertical) => go(ldir.turn_right()),
+            (MirrorMazeElement::MirrorSlash, Axis::Horizontal) => go(ldir.turn_left()),
+            (MirrorMazeElement::MirrorAntislash, Axis::Vertical) => go(ldir.turn_left()),
+            (MirrorMazeElement::MirrorAntislash, Axis::Horizontal) => go(ldir.turn_right()),
         }
     }
 }
@@ -124,6 +80,8 @@ impl From<&str> for Mirror {
 }
 
 type EnergizedMap = Vec<Vec<(bool, Option<Vec<Direction>>)>>;
+/// Start direction along with the x and y ranges of an edge of the map.
+type StartEdge = (Direction, (usize, usize), (usize, usize));
 impl Mirror {
     fn max_x(&self) -> usize {
         self.map[0].len() - 1
@@ -131,15 +89,31 @@ impl Mirror {
     fn max_y(&self) -> usize {
         self.map.len() - 1
     }
-    fn in_bounds(&self, x: usize, y: usize) -> bool {
-        y <= self.max_y() && x <= self.max_x()
+    fn in_bounds(&self, pos: Point) -> bool {
+        pos.y <= self.max_y() && pos.x <= self.max_x()
     }
-    fn simulate_laser(&self, start_pos: (usize, usize), start_dir: Direction) -> EnergizedMap {
+    /// Runs the beams depth first, or all together one step at a time when recording,
+    /// with a frame after each step.
+    fn simulate_laser<R: Recorder>(
+        &self,
+        start_pos: (usize, usize),
+        start_dir: Direction,
+        recorder: &mut R,
+    ) -> EnergizedMap {
         let mut energized_map: EnergizedMap =
             vec![vec![(false, None); self.map[0].len()]; self.map.len()];
-        let mut running_lasers = vec![(start_pos, start_dir)];
-        'laser_loop: while let Some(((lx, ly), dir)) = running_lasers.pop() {
-            let (currently_energized, energized_dirs_opt) = &mut energized_map[ly][lx];
+        let mut running_lasers = vec![(Point::from(start_pos), start_dir)];
+        let mut next_lasers = Vec::new();
+        'laser_loop: loop {
+            let Some((pos, dir)) = running_lasers.pop() else {
+                if next_lasers.is_empty() {
+                    break;
+                }
+                recorder.frame(|| self.frame(&energized_map, &next_lasers));
+                std::mem::swap(&mut running_lasers, &mut next_lasers);
+                continue;
+            };
+            let (currently_energized, energized_dirs_opt) = &mut energized_map[pos.y][pos.x];
             if let Some(energized_dirs) = energized_dirs_opt {
                 for energized_dir in energized_dirs.iter() {
                     if *energized_dir == dir {
@@ -153,28 +127,70 @@ impl Mirror {
             *currently_energized = true;
             energized_dirs_opt.as_mut().unwrap().push(dir);
 
+            let lasers = if R::ENABLED {
+                &mut next_lasers
+            } else {
+                &mut running_lasers
+            };
             // TODO - maybe optimise for empty tiles with a loop here
-            if let Some(element) = self.map[ly][lx] {
-                element.get_next_pos(lx, ly, dir, |new_lx, new_ly, new_dir| {
-                    if self.in_bounds(new_lx, new_ly) {
-                        running_lasers.push(((new_lx, new_ly), new_dir));
+            if let Some(element) = self.map[pos.y][pos.x] {
+                element.get_next_pos(pos, dir, |new_pos, new_dir| {
+                    if self.in_bounds(new_pos) {
+                        lasers.push((new_pos, new_dir));
                     }
                 })
-            } else if let Some((new_lx, new_ly)) = dir.apply_delta((lx, ly)) {
-                if self.in_bounds(new_lx, new_ly) {
-                    running_lasers.push(((new_lx, new_ly), dir));
+            } else if let Some(new_pos) = pos.checked_step(dir) {
+                if self.in_bounds(new_pos) {
+                    lasers.push((new_pos, dir));
                 }
             }
         }
+        recorder.frame(|| self.frame(&energized_map, &[]));
         energized_map
     }
-    fn simulater_laser_count(&self, start_pos: (usize, usize), start_dir: Direction) -> usize {
-        self.simulate_laser(start_pos, start_dir)
+
+    /// Beam heads as arrows over the energized tiles.
+    fn frame(&self, energized_map: &EnergizedMap, lasers: &[(Point, Direction)]) -> Frame {
+        let mut frame = Grid::from_fn(self.max_x() + 1, self.max_y() + 1, |(x, y)| {
+            let char = self.map[y][x].map_or('.', |element| element.char());
+            if energized_map[y][x].0 {
+                Cell::new(char, Rgb::new(255, 200, 40))
+            } else {
+                Cell::new(char, Rgb::gray(90))
+            }
+        });
+        for &(pos, dir) in lasers {
+            frame[(pos.x, pos.y)] = Cell::new(dir.arrow(), Rgb::new(255, 60, 60));
+        }
+        Frame::from_grid(&frame, |_, &cell| cell)
+    }
+
+    fn simulater_laser_count(
+        &self,
+        start_pos: (usize, usize),
+        start_dir: Direction,
+        recorder: &mut impl Recorder,
+    ) -> usize {
+        self.simulate_laser(start_pos, start_dir, recorder)
             .into_iter()
             .map(|line| line.into_iter().filter(|e| e.0).count())
             .sum()
     }
 
+    /// Mirrors and splitters in white, energized tiles brighter the more beam directions cross them.
+    fn render(&self, energized_map: &EnergizedMap, scale: usize) -> Image {
+        Image::from_fn(self.max_x() + 1, self.max_y() + 1, scale, |(x, y)| {
+            match (self.map[y][x], &energized_map[y][x]) {
+                (Some(_), _) => Rgb::WHITE,
+                (None, (true, dirs)) => {
+                    let crossings = dirs.as_ref().map_or(1, Vec::len);
+                    Rgb::new(120, 60, 0).lerp(Rgb::new(255, 220, 60), crossings as f64 / 4.0)
+                }
+                (None, (false, _)) => Rgb::gray(30),
+            }
+        })
+    }
+
     fn simulater_laser_maximise(&self) -> usize {
         fn merge_history(history: &mut EnergizedMap, to_merge: EnergizedMap) {
             to_merge.into_iter().enumerate().for_each(|(y, line)| {
@@ -200,7 +216,7 @@ impl Mirror {
         }
         let mut history: Option<EnergizedMap> = None;
         let mut max_count = 0;
-        let mut around_data: [(Direction, (usize, usize), (usize, usize)); 4] = [
+        let mut around_data: [StartEdge; 4] = [
             (Direction::Down, (0, self.max_x()), (0, 0)),
             (
                 Direction::Left,
@@ -232,31 +248,23 @@ impl Mirror {
                                     continue 'start_loop,
                                 }
                                 */
-                                match (*sdir, *hdir) {
-                                    (
-                                        Direction::Down | Direction::Up,
-                                        Direction::Down | Direction::Up,
-                                    ) => continue 'start_loop,
-                                    (
-                                        Direction::Right | Direction::Left,
-                                        Direction::Right | Direction::Left,
-                                    ) => continue 'start_loop,
-                                    _ => (),
+                                if sdir.axis() == hdir.axis() {
+                                    continue 'start_loop;
                                 }
                             }
                         }
                     }
 
-                    let new_history = self.simulate_laser((sx, sy), *sdir);
+                    let new_history = self.simulate_laser((sx, sy), *sdir, &mut ());
                     let count = new_history
                         .iter()
                         .map(|line| line.iter().filter(|e| e.0).count())
                         .sum();
                     max_count = max_count.max(count);
-                    if history.is_none() {
-                        history = Some(new_history);
+                    if let Some(history) = history.as_mut() {
+                        merge_history(history, new_history);
                     } else {
-                        merge_history(&mut history.as_mut().unwrap(), new_history);
+                        history = Some(new_history);
                     }
                 }
             }
@@ -267,7 +275,7 @@ impl Mirror {
 
 pub fn part_one(input: &str) -> Option<usize> {
     let mirror = Mirror::from(input);
-    Some(mirror.simulater_laser_count((0, 0), Direction::Right))
+    Some(mirror.simulater_laser_count((0, 0), Direction::Right, &mut ()))
 }
 
 // 8315
@@ -277,6 +285,21 @@ pub fn part_two(input: &str) -> Option<usize> {
     Some(mirror.simulater_laser_maximise())
 }
 
+/// Records the beams of part one with `--record` or `--play`, and renders them with `--render`.
+fn extras(input: &str, options: &Options) {
+    if let Some(mut recording) = Recording::from_options(options) {
+        Mirror::from(input).simulater_laser_count((0, 0), Direction::Right, &mut recording);
+        recording.finish();
+    }
+    render::render_requested(options, |scale| {
+        let mirror = Mirror::from(input);
+        mirror.render(
+            &mirror.simulate_laser((0, 0), Direction::Right, &mut ()),
+            scale,
+        )
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
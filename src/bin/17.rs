@@ -1,191 +1,105 @@
-use std::collections::VecDeque;
-
-advent_of_code::solution!(17);
-
-#[derive(Clone, Copy)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
+use advent_of_code::grid::Grid;
+use advent_of_code::point::{Axis, Direction, Point};
+use advent_of_code::render::{self, Image, Rgb};
+use advent_of_code::search;
+use advent_of_code::template::Options;
+
+advent_of_code::solution!(17, extras);
+
+/// Positions `n.0..=n.1` steps away from `pos` towards `dir`, as long as they do not underflow.
+fn steps(pos: (usize, usize), dir: Direction, n: (usize, usize)) -> Vec<(usize, usize)> {
+    (n.0..=n.1)
+        .map_while(|c| Point::from(pos).checked_step_n(dir, c))
+        .map(Into::into)
+        .collect()
 }
 
-impl Direction {
-    fn apply_delta(&self, pos: (usize, usize), n: (usize, usize)) -> Vec<(usize, usize)> {
-        (n.0..=n.1)
-            .filter_map(|c| match self {
-                Direction::Up if pos.1 >= c => Some((pos.0, pos.1 - c)),
-                Direction::Right => Some((pos.0 + c, pos.1)),
-                Direction::Down => Some((pos.0, pos.1 + c)),
-                Direction::Left if pos.0 >= c => Some((pos.0 - c, pos.1)),
-                _ => None,
-            })
-            .collect()
-    }
-}
+/// A block with the axis the crucible moved along to get there, it has to turn onto the other one.
+type State = ((usize, usize), Option<Axis>);
 
 #[derive(Debug)]
 struct CityBlockMap {
-    map: Vec<Vec<u8>>,
+    map: Grid<u8>,
 }
 
 impl From<&str> for CityBlockMap {
     fn from(value: &str) -> Self {
         CityBlockMap {
-            map: value
-                .lines()
-                .map(|l| l.chars().map(|c| c.to_digit(10).unwrap() as u8).collect())
-                .collect(),
+            map: Grid::parse(value, |c| c.to_digit(10).unwrap() as u8).unwrap(),
         }
     }
 }
 
 impl CityBlockMap {
-    fn max_x(&self) -> usize {
-        self.map[0].len() - 1
-    }
-    fn max_y(&self) -> usize {
-        self.map.len() - 1
-    }
-    fn in_bounds(&self, pos: &(usize, usize)) -> bool {
-        pos.0 <= self.max_x() && pos.1 <= self.max_y()
+    /// Least heat loss from the top left to the bottom right block, with a crucible going
+    /// `moves.0..=moves.1` blocks straight before turning.
+    fn shortest_heat(&self, moves: (usize, usize)) -> Option<usize> {
+        self.search(moves).cost()
     }
-    fn shortest_heat_p1(&self) -> usize {
-        struct State {
-            pos: (usize, usize),
-            heat_loss: usize,
-            last_dir: Option<Direction>,
-        }
 
-        let mut states: VecDeque<State> = VecDeque::new();
-        states.push_back(State {
-            pos: (0, 0),
-            heat_loss: 0,
-            last_dir: None,
-        });
-        let mut best_history_hori: Vec<Vec<usize>> =
-            vec![vec![usize::MAX; self.map[0].len()]; self.map.len()];
-        let mut best_history_verti: Vec<Vec<usize>> =
-            vec![vec![usize::MAX; self.map[0].len()]; self.map.len()];
-        while let Some(state) = states.pop_front() {
-            let new_dirs = match state.last_dir {
+    fn search(&self, moves: (usize, usize)) -> search::Visited<State> {
+        let neighbours = |&(pos, axis): &State| {
+            let new_dirs = match axis {
                 None => [Direction::Down, Direction::Right],
-                Some(Direction::Down | Direction::Up) => [Direction::Left, Direction::Right],
-                Some(Direction::Left | Direction::Right) => [Direction::Up, Direction::Down],
+                Some(Axis::Horizontal) => [Direction::Up, Direction::Down],
+                Some(Axis::Vertical) => [Direction::Left, Direction::Right],
             };
-
+            let mut next = Vec::with_capacity(2 * (moves.1 - moves.0 + 1));
             for new_dir in new_dirs {
                 let mut extra_heat_loss = 0;
-                for new_pos in new_dir.apply_delta(state.pos, (1, 3)) {
-                    if !self.in_bounds(&new_pos) {
-                        continue;
+                for (n, new_pos) in steps(pos, new_dir, (1, moves.1)).into_iter().enumerate() {
+                    if !self.map.in_bounds(new_pos) {
+                        break;
                     }
-                    extra_heat_loss += self.map[new_pos.1][new_pos.0] as usize;
-                    let new_heat_loss = extra_heat_loss + state.heat_loss;
-                    let best_history = match new_dir {
-                        Direction::Up | Direction::Down => &mut best_history_verti,
-                        Direction::Right | Direction::Left => &mut best_history_hori,
-                    };
-                    let old_heat_loss = best_history
-                        .get_mut(new_pos.1)
-                        .unwrap()
-                        .get_mut(new_pos.0)
-                        .unwrap();
-
-                    if new_heat_loss < *old_heat_loss {
-                        *old_heat_loss = new_heat_loss;
-                        states.push_back(State {
-                            pos: new_pos,
-                            heat_loss: new_heat_loss,
-                            last_dir: Some(new_dir),
-                        })
+                    extra_heat_loss += self.map[new_pos] as usize;
+                    if n + 1 >= moves.0 {
+                        next.push(((new_pos, Some(new_dir.axis())), extra_heat_loss));
                     }
                 }
             }
-        }
-        best_history_hori[self.max_y()][self.max_x()]
-            .min(best_history_verti[self.max_y()][self.max_x()])
+            next
+        };
+        let goal = self.map.max_pos();
+        search::dijkstra_buckets([((0, 0), None)], neighbours, |&(pos, _)| pos == goal)
     }
-    fn shortest_heat_p2(&self) -> usize {
-        struct State {
-            pos: (usize, usize),
-            heat_loss: usize,
-            last_dir: Option<Direction>,
-        }
 
-        let mut states: VecDeque<State> = VecDeque::new();
-        states.push_back(State {
-            pos: (0, 0),
-            heat_loss: 0,
-            last_dir: None,
-        });
-        let mut best_history_hori: Vec<Vec<usize>> =
-            vec![vec![usize::MAX; self.map[0].len()]; self.map.len()];
-        let mut best_history_verti: Vec<Vec<usize>> =
-            vec![vec![usize::MAX; self.map[0].len()]; self.map.len()];
-        while let Some(state) = states.pop_front() {
-            let new_dirs = match state.last_dir {
-                None => [Direction::Down, Direction::Right],
-                Some(Direction::Down | Direction::Up) => [Direction::Left, Direction::Right],
-                Some(Direction::Left | Direction::Right) => [Direction::Up, Direction::Down],
-            };
-
-            for new_dir in new_dirs {
-                let new_pos_list = new_dir.apply_delta(state.pos, (1, 10));
-                if new_pos_list.len() < 4 {
-                    continue;
-                }
-                let mut extra_heat_loss: usize = new_pos_list
-                    .iter()
-                    .take(3)
-                    .filter_map(|(x, y)| {
-                        if self.in_bounds(&(*x, *y)) {
-                            Some(self.map[*y][*x] as usize)
-                        } else {
-                            None
-                        }
-                    })
-                    .sum();
-                for new_pos in new_pos_list[3..].into_iter() {
-                    if !self.in_bounds(&new_pos) {
-                        continue;
-                    }
-                    extra_heat_loss += self.map[new_pos.1][new_pos.0] as usize;
-                    let new_heat_loss = extra_heat_loss + state.heat_loss;
-                    let best_history = match new_dir {
-                        Direction::Up | Direction::Down => &mut best_history_verti,
-                        Direction::Right | Direction::Left => &mut best_history_hori,
-                    };
-                    let old_heat_loss = best_history
-                        .get_mut(new_pos.1)
-                        .unwrap()
-                        .get_mut(new_pos.0)
-                        .unwrap();
-
-                    if new_heat_loss < *old_heat_loss {
-                        *old_heat_loss = new_heat_loss;
-                        states.push_back(State {
-                            pos: *new_pos,
-                            heat_loss: new_heat_loss,
-                            last_dir: Some(new_dir),
-                        })
-                    }
+    /// Heat loss in shades of red, with the best path of the crucible in white.
+    fn render(&self, moves: (usize, usize), scale: usize) -> Image {
+        let mut on_path = Grid::new(self.map.width(), self.map.height(), false);
+        let turns = self.search(moves).path().unwrap_or_default();
+        for pair in turns.windows(2) {
+            let ((from, _), (to, _)) = (pair[0], pair[1]);
+            for x in from.0.min(to.0)..=from.0.max(to.0) {
+                for y in from.1.min(to.1)..=from.1.max(to.1) {
+                    on_path[(x, y)] = true;
                 }
             }
         }
-        best_history_hori[self.max_y()][self.max_x()]
-            .min(best_history_verti[self.max_y()][self.max_x()])
+        Image::from_grid(&self.map, scale, |pos, &heat| {
+            if on_path[pos] {
+                Rgb::WHITE
+            } else {
+                Rgb::new(40, 0, 0).lerp(Rgb::new(255, 90, 0), f64::from(heat) / 9.0)
+            }
+        })
     }
 }
 
 pub fn part_one(input: &str) -> Option<usize> {
     let city = CityBlockMap::from(input);
-    Some(city.shortest_heat_p1())
+    city.shortest_heat((1, 3))
 }
 
 pub fn part_two(input: &str) -> Option<usize> {
     let city = CityBlockMap::from(input);
-    Some(city.shortest_heat_p2())
+    city.shortest_heat((4, 10))
+}
+
+/// Renders the path of part one with `--render`.
+fn extras(input: &str, options: &Options) {
+    render::render_requested(options, |scale| {
+        CityBlockMap::from(input).render((1, 3), scale)
+    });
 }
 
 #[cfg(test)]
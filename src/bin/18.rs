@@ -1,5 +1,3 @@
-use core::panic;
-
 use nom::{
     bytes::complete::tag,
     character::complete::anychar,
@@ -8,44 +6,19 @@ use nom::{
     sequence::{delimited, terminated, tuple},
 };
 
-advent_of_code::solution!(18);
-
-#[derive(Clone, Copy)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
+use advent_of_code::geometry::Polygon;
+use advent_of_code::grid::Grid;
+use advent_of_code::point::{Direction, IPoint};
+use advent_of_code::render::{self, Image, Rgb};
+use advent_of_code::search;
+use advent_of_code::template::Options;
 
-impl From<char> for Direction {
-    fn from(value: char) -> Self {
-        match value {
-            'U' => Self::Up,
-            'R' => Self::Right,
-            'D' => Self::Down,
-            'L' => Self::Left,
-            _ => panic!("invalid character for Direction"),
-        }
-    }
-}
+advent_of_code::solution!(18, extras);
 
 struct Instruction {
     dir: Direction,
     length: usize,
-    _color: [u8; 3],
-}
-
-impl Instruction {
-    fn get_new_pos(&self, current_pos: &(isize, isize)) -> (isize, isize) {
-        let length = self.length as isize;
-        match self.dir {
-            Direction::Up => (current_pos.0, current_pos.1 - length),
-            Direction::Right => (current_pos.0 + length, current_pos.1),
-            Direction::Down => (current_pos.0, current_pos.1 + length),
-            Direction::Left => (current_pos.0 - length, current_pos.1),
-        }
-    }
+    color: [u8; 3],
 }
 
 impl From<&str> for Instruction {
@@ -57,11 +30,10 @@ impl From<&str> for Instruction {
                 delimited(tag("(#"), alphanumeric1, tag(")")),
             )),
             |(dir_char, length_str, color_str)| Instruction {
-                dir: Direction::from(dir_char),
-                length: usize::from_str_radix(length_str, 10).unwrap(),
-                _color: {
+                dir: Direction::try_from(dir_char).unwrap(),
+                length: length_str.parse::<usize>().unwrap(),
+                color: {
                     let mut iter = (0..color_str.len())
-                        .into_iter()
                         .step_by(2)
                         .map(|i| u8::from_str_radix(&color_str[i..i + 2], 16).unwrap());
                     [
@@ -90,7 +62,7 @@ impl From<&str> for DigInstructionList {
 }
 
 impl DigInstructionList {
-    fn to_part_two_instructions(self) -> Self {
+    fn into_part_two_instructions(self) -> Self {
         DigInstructionList {
             instrs: self
                 .instrs
@@ -101,91 +73,235 @@ impl DigInstructionList {
                         Direction::Down,
                         Direction::Left,
                         Direction::Up,
-                    ][(ins._color[2] & 0x0f) as usize],
-                    length: ((ins._color[0] as usize) << 12)
-                        + ((ins._color[1] as usize) << 4)
-                        + ((ins._color[2] as usize & 0xf0) >> 4) as usize,
-                    _color: [0; 3],
+                    ][(ins.color[2] & 0x0f) as usize],
+                    length: ((ins.color[0] as usize) << 12)
+                        + ((ins.color[1] as usize) << 4)
+                        + ((ins.color[2] as usize & 0xf0) >> 4),
+                    color: [0; 3],
                 })
                 .collect(),
         }
     }
-    fn get_hole_size(&self) -> usize {
-        fn _shoelace_formula(points: &Vec<(isize, isize)>) -> usize {
-            assert_eq!(points.first(), points.last());
-            let mut sum = 0;
-            for i in 0..(points.len() - 1) {
-                let p1 = points[i];
-                let p2 = points[i + 1];
-                sum += (p1.1 + p2.1) * (p1.0 - p2.0)
-            }
-            assert!(sum >= 0);
-            return (sum / 2) as usize;
-        }
-
-        let mut max_x = 0;
-        let mut min_x = 0;
-        let mut max_y = 0;
-        let mut min_y = 0;
-        let mut x = 0;
-        let mut y = 0;
+    fn get_hole_size(&self) -> Option<usize> {
+        // the trench goes through the centers of the dug cubes, each lattice point is a cube.
+        let polygon = Polygon::from_walk(
+            IPoint::default(),
+            self.instrs.iter().map(|instr| (instr.dir, instr.length)),
+        );
+        polygon.lattice_points()?.try_into().ok()
+    }
 
+    /// The trench in the colors of its instructions, around the dug out interior.
+    /// Digs cell by cell, so only fit for the part one plans.
+    fn render(&self, scale: usize) -> Image {
+        let mut trench = vec![(IPoint::default(), Rgb::BLACK)];
         for instr in &self.instrs {
-            match instr.dir {
-                Direction::Right => {
-                    x += instr.length as isize;
-                    max_x = max_x.max(x);
-                }
-                Direction::Left => {
-                    x -= instr.length as isize;
-                    min_x = min_x.min(x);
-                }
-                Direction::Down => {
-                    y += instr.length as isize;
-                    max_y = max_y.max(y);
-                }
-                Direction::Up => {
-                    y -= instr.length as isize;
-                    min_y = min_y.min(y);
-                }
+            for _ in 0..instr.length {
+                let pos = trench.last().unwrap().0.step(instr.dir);
+                trench.push((pos, Rgb::from(instr.color)));
             }
         }
-        let mut points = Vec::new();
-        let init_pos = (-min_x, -min_y);
-        points.push(init_pos);
-        let mut current_pos = init_pos;
-
-        for instr in &self.instrs {
-            current_pos = instr.get_new_pos(&current_pos);
-            points.push(current_pos);
+        // a margin of one cell around the trench, so the outside is connected.
+        let min_x = trench.iter().map(|(p, _)| p.x).min().unwrap() - 1;
+        let min_y = trench.iter().map(|(p, _)| p.y).min().unwrap() - 1;
+        let max_x = trench.iter().map(|(p, _)| p.x).max().unwrap() + 1;
+        let max_y = trench.iter().map(|(p, _)| p.y).max().unwrap() + 1;
+        let (width, height) = ((max_x - min_x + 1) as usize, (max_y - min_y + 1) as usize);
+        let mut colors = Grid::new(width, height, None);
+        for (pos, color) in trench {
+            colors[((pos.x - min_x) as usize, (pos.y - min_y) as usize)] = Some(color);
         }
-        let mut area = _shoelace_formula(&points);
-        // add missing edge:
-        self.instrs.iter().for_each(|ins| match ins.dir {
-            Direction::Right | Direction::Up => area += ins.length,
-            _ => (),
-        });
-        // I miss 1, who knows
-        area + 1
+        let outside = search::bfs(
+            [(0, 0)],
+            |&pos| colors.neighbours_4(pos).filter(|&n| colors[n].is_none()),
+            |_| false,
+        );
+        for (&pos, _) in outside.iter() {
+            colors[pos] = Some(Rgb::gray(30));
+        }
+        Image::from_grid(&colors, scale, |_, color| {
+            color.unwrap_or(Rgb::new(194, 160, 110))
+        })
     }
 }
 
 pub fn part_one(input: &str) -> Option<usize> {
-    Some(DigInstructionList::from(input).get_hole_size())
+    DigInstructionList::from(input).get_hole_size()
 }
 
 pub fn part_two(input: &str) -> Option<usize> {
-    Some(
-        DigInstructionList::from(input)
-            .to_part_two_instructions()
-            .get_hole_size(),
-    )
+    DigInstructionList::from(input)
+        .into_part_two_instructions()
+        .get_hole_size()
+}
+
+/// Renders the lagoon of part one with `--render`.
+fn extras(input: &str, options: &Options) {
+    render::render_requested(options, |scale| {
+        DigInstructionList::from(input).render(scale)
+    });
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+    use std::fmt::Display;
+
+    use advent_of_code::generate::shapes::{polygon_columns, polygon_outline};
+    use advent_of_code::property::{self, shrink_u64, shrink_vec, Arbitrary};
+    use advent_of_code::random::Rng;
+
     use super::*;
 
+    /// A clockwise x-monotone lagoon: a list of columns `(width, top, bottom)`.
+    /// Every column is crossed by a single vertical slice of the lagoon.
+    #[derive(Clone)]
+    struct LagoonCase {
+        columns: Vec<(u64, i64, i64)>,
+        /// Quarter turns applied to the whole plan, turning keeps the orientation.
+        rotation: usize,
+        /// Index of the first instruction, the plan is a loop.
+        start: usize,
+    }
+
+    const DIRECTIONS: [char; 4] = ['R', 'D', 'L', 'U'];
+
+    impl LagoonCase {
+        fn is_valid(&self) -> bool {
+            !self.columns.is_empty()
+                && self
+                    .columns
+                    .iter()
+                    .all(|&(w, top, bottom)| w > 0 && top < bottom)
+                && self.columns.windows(2).all(|pair| {
+                    let (_, top1, bottom1) = pair[0];
+                    let (_, top2, bottom2) = pair[1];
+                    top1.max(top2) < bottom1.min(bottom2)
+                })
+        }
+
+        /// Instructions as `(direction index in DIRECTIONS, length)`.
+        fn plan(&self) -> Vec<(usize, u64)> {
+            let mut plan: Vec<(usize, u64)> = polygon_outline(&self.columns)
+                .into_iter()
+                .map(|(dir, length)| {
+                    let dir = DIRECTIONS.iter().position(|&d| d == dir).unwrap();
+                    ((dir + self.rotation) % 4, length)
+                })
+                .collect();
+            let start = self.start % plan.len();
+            plan.rotate_left(start);
+            plan
+        }
+
+        /// Digs the trench cell by cell and flood fills the outside.
+        fn brute_force_volume(&self) -> usize {
+            let mut trench = HashSet::new();
+            let mut pos = (0_i64, 0_i64);
+            trench.insert(pos);
+            for (dir, length) in self.plan() {
+                let delta = [(1, 0), (0, 1), (-1, 0), (0, -1)][dir];
+                for _ in 0..length {
+                    pos = (pos.0 + delta.0, pos.1 + delta.1);
+                    trench.insert(pos);
+                }
+            }
+            let min_x = trench.iter().map(|p| p.0).min().unwrap() - 1;
+            let max_x = trench.iter().map(|p| p.0).max().unwrap() + 1;
+            let min_y = trench.iter().map(|p| p.1).min().unwrap() - 1;
+            let max_y = trench.iter().map(|p| p.1).max().unwrap() + 1;
+
+            let mut outside = HashSet::from([(min_x, min_y)]);
+            let mut stack = vec![(min_x, min_y)];
+            while let Some((x, y)) = stack.pop() {
+                for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                    if (min_x..=max_x).contains(&nx)
+                        && (min_y..=max_y).contains(&ny)
+                        && !trench.contains(&(nx, ny))
+                        && outside.insert((nx, ny))
+                    {
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+            let area = (max_x - min_x + 1) * (max_y - min_y + 1);
+            area as usize - outside.len()
+        }
+    }
+
+    impl Display for LagoonCase {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            // the color encodes the same plan, so both parts dig the same lagoon.
+            for (dir, length) in self.plan() {
+                writeln!(f, "{} {length} (#{length:05x}{dir})", DIRECTIONS[dir])?;
+            }
+            Ok(())
+        }
+    }
+
+    impl Arbitrary for LagoonCase {
+        fn arbitrary(rng: &mut Rng, size: usize) -> Self {
+            let count = rng.range_usize(1, size + 1);
+            LagoonCase {
+                columns: polygon_columns(rng, count, size as u64 + 2),
+                rotation: rng.range_usize(0, 3),
+                start: rng.range_usize(0, 4 * size + 4),
+            }
+        }
+
+        fn shrink(&self) -> Vec<Self> {
+            let mut candidates: Vec<Self> = shrink_vec(&self.columns, 1, |&(w, top, bottom)| {
+                let mut smaller: Vec<_> = shrink_u64(w, 1)
+                    .into_iter()
+                    .map(|w| (w, top, bottom))
+                    .collect();
+                smaller.push((w, top, bottom - 1));
+                smaller.push((w, top + 1, bottom));
+                smaller
+            })
+            .into_iter()
+            .map(|columns| LagoonCase {
+                columns,
+                ..self.clone()
+            })
+            .collect();
+            if self.rotation != 0 {
+                candidates.push(LagoonCase {
+                    rotation: 0,
+                    ..self.clone()
+                });
+            }
+            if self.start != 0 {
+                candidates.push(LagoonCase {
+                    start: 0,
+                    ..self.clone()
+                });
+            }
+            candidates.retain(LagoonCase::is_valid);
+            candidates
+        }
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        property::check(|case: &LagoonCase| {
+            if !case.is_valid() {
+                return Err("generated an invalid lagoon".into());
+            }
+            let input = case.to_string();
+            let expected = Some(case.brute_force_volume());
+            let result = part_one(&input);
+            if result != expected {
+                return Err(format!("part one: expected {expected:?}, got {result:?}"));
+            }
+            let result = part_two(&input);
+            if result != expected {
+                return Err(format!("part two: expected {expected:?}, got {result:?}"));
+            }
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
@@ -0,0 +1,263 @@
+/// Cycle detection for deterministic processes, to jump to the state after a huge number of steps.
+///
+/// A process going from state to state through `step` ends up looping over the same states
+/// as soon as one repeats. The hashed variants remember every state and find the cycle as soon
+/// as it closes; the Brent variants only keep two states but step about three times as much.
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// States after `prefix` steps repeat every `period` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The first step with the same state as the one after `n` steps.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.prefix {
+            n
+        } else {
+            self.prefix + (n - self.prefix) % self.period
+        }
+    }
+}
+
+/// The state after the requested number of steps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Jump<S> {
+    pub state: S,
+    /// The cycle, if it closed before the requested number of steps.
+    pub cycle: Option<Cycle>,
+    /// Calls to `step` made to get there.
+    pub steps: usize,
+}
+
+/// Either the cycle with the state after `prefix + period` steps, or the state after `limit` steps
+/// if the cycle did not close before.
+type Detection<S> = Result<(Cycle, S), S>;
+
+fn detect_hashed<S>(start: S, limit: usize, step: &mut impl FnMut(&S) -> S) -> Detection<S>
+where
+    S: Clone + Eq + Hash,
+{
+    let mut seen = HashMap::new();
+    let mut state = start;
+    for i in 0..limit {
+        match seen.entry(state.clone()) {
+            Entry::Occupied(entry) => {
+                let prefix = *entry.get();
+                let period = i - prefix;
+                return Ok((Cycle { prefix, period }, state));
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(i);
+            }
+        }
+        state = step(&state);
+    }
+    if seen.contains_key(&state) {
+        let prefix = seen[&state];
+        return Ok((
+            Cycle {
+                prefix,
+                period: limit - prefix,
+            },
+            state,
+        ));
+    }
+    Err(state)
+}
+
+fn detect_brent<S>(start: S, limit: usize, step: &mut impl FnMut(&S) -> S) -> (Detection<S>, usize)
+where
+    S: Clone + Eq,
+{
+    if limit == 0 {
+        return (Err(start), 0);
+    }
+    // the hare runs ahead, the tortoise teleports to it at every power of two.
+    let (mut power, mut period) = (1, 1);
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    let mut steps = 1;
+    while tortoise != hare {
+        if steps == limit {
+            return (Err(hare), steps);
+        }
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        steps += 1;
+        period += 1;
+    }
+
+    // with a `period` head start, the hare meets the tortoise where the cycle begins.
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut prefix = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+    steps += period + 2 * prefix;
+    (Ok((Cycle { prefix, period }, hare)), steps)
+}
+
+/// Moves a state known to be after `prefix + period` steps to the one after `n` steps.
+fn fast_forward<S>(
+    cycle: Cycle,
+    mut state: S,
+    n: usize,
+    step: &mut impl FnMut(&S) -> S,
+) -> (S, usize) {
+    let remaining = (n - cycle.prefix - cycle.period) % cycle.period;
+    for _ in 0..remaining {
+        state = step(&state);
+    }
+    (state, remaining)
+}
+
+/// Finds the cycle of the states from `start`, remembering all of them.
+/// Never returns if the states never repeat.
+pub fn find<S>(start: S, mut step: impl FnMut(&S) -> S) -> Cycle
+where
+    S: Clone + Eq + Hash,
+{
+    match detect_hashed(start, usize::MAX, &mut step) {
+        Ok((cycle, _)) => cycle,
+        Err(_) => unreachable!("{} steps without a cycle", usize::MAX),
+    }
+}
+
+/// Finds the cycle of the states from `start`, with Brent's algorithm.
+/// Never returns if the states never repeat.
+pub fn find_brent<S>(start: S, mut step: impl FnMut(&S) -> S) -> Cycle
+where
+    S: Clone + Eq,
+{
+    match detect_brent(start, usize::MAX, &mut step).0 {
+        Ok((cycle, _)) => cycle,
+        Err(_) => unreachable!("{} steps without a cycle", usize::MAX),
+    }
+}
+
+/// The state after `n` steps from `start`, skipping whole periods once the states repeat.
+pub fn nth<S>(start: S, n: usize, mut step: impl FnMut(&S) -> S) -> Jump<S>
+where
+    S: Clone + Eq + Hash,
+{
+    match detect_hashed(start, n, &mut step) {
+        Ok((cycle, state)) => {
+            let (state, remaining) = fast_forward(cycle, state, n, &mut step);
+            Jump {
+                state,
+                cycle: Some(cycle),
+                steps: cycle.prefix + cycle.period + remaining,
+            }
+        }
+        Err(state) => Jump {
+            state,
+            cycle: None,
+            steps: n,
+        },
+    }
+}
+
+/// Like [`nth`], with Brent's algorithm: it only needs `S: Eq` and two states in memory.
+pub fn nth_brent<S>(start: S, n: usize, mut step: impl FnMut(&S) -> S) -> Jump<S>
+where
+    S: Clone + Eq,
+{
+    match detect_brent(start, n, &mut step) {
+        (Ok((cycle, state)), steps) => {
+            let (state, remaining) = fast_forward(cycle, state, n, &mut step);
+            Jump {
+                state,
+                cycle: Some(cycle),
+                steps: steps + remaining,
+            }
+        }
+        (Err(state), steps) => Jump {
+            state,
+            cycle: None,
+            steps,
+        },
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{find, find_brent, nth, nth_brent, Cycle};
+    use crate::random::Rng;
+
+    /// The cycle of `start` under `next`, by looking for the first repeat of every prefix.
+    fn brute_cycle(next: &[usize], start: usize) -> Cycle {
+        let mut states = vec![start];
+        loop {
+            let state = next[*states.last().unwrap()];
+            if let Some(prefix) = states.iter().position(|&s| s == state) {
+                return Cycle {
+                    prefix,
+                    period: states.len() - prefix,
+                };
+            }
+            states.push(state);
+        }
+    }
+
+    #[test]
+    fn matches_brute_force_on_random_functions() {
+        let mut rng = Rng::new(36);
+        for _ in 0..200 {
+            let size = rng.range_usize(1, 30);
+            let next: Vec<usize> = (0..size).map(|_| rng.range_usize(0, size - 1)).collect();
+            let start = rng.range_usize(0, size - 1);
+            let step = |&s: &usize| next[s];
+
+            let expected = brute_cycle(&next, start);
+            assert_eq!(find(start, step), expected);
+            assert_eq!(find_brent(start, step), expected);
+
+            let mut state = start;
+            for n in 0..3 * size {
+                for jump in [nth(start, n, step), nth_brent(start, n, step)] {
+                    assert_eq!(jump.state, state, "{next:?} from {start}, {n} steps");
+                    if let Some(cycle) = jump.cycle {
+                        assert_eq!(cycle, expected);
+                    }
+                    assert!(jump.steps <= n + 5 * size);
+                }
+                state = next[state];
+            }
+        }
+    }
+
+    #[test]
+    fn jumps_far() {
+        // 0, 1, 2, then 3 to 10 over and over.
+        let step = |&s: &usize| if s < 10 { s + 1 } else { 3 };
+        let expected = Cycle {
+            prefix: 3,
+            period: 8,
+        };
+        let n = 1_000_000_000_000;
+        for jump in [nth(0, n, step), nth_brent(0, n, step)] {
+            assert_eq!(jump.cycle, Some(expected));
+            assert_eq!(jump.state, 3 + (n - 3) % 8);
+            assert!(jump.steps < 100);
+        }
+        assert_eq!(expected.reduce(2), 2);
+        assert_eq!(expected.reduce(11), 3);
+        assert_eq!(nth(0, 5, step).cycle, None);
+    }
+}
@@ -0,0 +1,621 @@
+/// One generator per solved day, see [`super::generate`].
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use super::shapes::{
+    polygon_columns, polygon_outline, record_groups, record_line, record_springs,
+};
+use crate::random::Rng;
+
+fn grid(
+    rng: &mut Rng,
+    width: usize,
+    height: usize,
+    mut cell: impl FnMut(&mut Rng) -> char,
+) -> String {
+    let mut out = String::with_capacity((width + 1) * height);
+    for _ in 0..height {
+        out.extend((0..width).map(|_| cell(rng)));
+        out.push('\n');
+    }
+    out
+}
+
+fn join<T: ToString>(values: impl IntoIterator<Item = T>, separator: &str) -> String {
+    values
+        .into_iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+pub fn calibration_document(rng: &mut Rng, size: usize) -> String {
+    const WORDS: [&str; 9] = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+    let mut out = String::new();
+    for _ in 0..size {
+        let mut line = String::new();
+        while line.len() < rng.range_usize(3, 40) {
+            match rng.range(0, 9) {
+                0 | 1 => line.push(char::from(b'1' + rng.range(0, 8) as u8)),
+                2 => {
+                    let word = *rng.choose(&WORDS);
+                    line.push_str(word);
+                }
+                _ => line.push(char::from(b'a' + rng.range(0, 25) as u8)),
+            }
+        }
+        // part one needs at least one actual digit on every line.
+        if !line.bytes().any(|b| b.is_ascii_digit()) {
+            let at = rng.range_usize(0, line.len());
+            line.insert(at, char::from(b'1' + rng.range(0, 8) as u8));
+        }
+        writeln!(out, "{line}").unwrap();
+    }
+    out
+}
+
+pub fn cube_games(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for game in 1..=size {
+        let handfuls: Vec<String> = (0..rng.range(1, 6))
+            .map(|_| {
+                let mut colors = vec!["red", "green", "blue"];
+                rng.shuffle(&mut colors);
+                colors.truncate(rng.range_usize(1, 3));
+                join(
+                    colors
+                        .iter()
+                        .map(|color| format!("{} {color}", rng.range(1, 20))),
+                    ", ",
+                )
+            })
+            .collect();
+        writeln!(out, "Game {game}: {}", handfuls.join("; ")).unwrap();
+    }
+    out
+}
+
+pub fn engine_schematic(rng: &mut Rng, size: usize) -> String {
+    const SYMBOLS: [char; 10] = ['*', '#', '+', '$', '/', '@', '=', '%', '&', '-'];
+    let width = size.max(3);
+    let mut out = String::new();
+    for _ in 0..width {
+        let mut x = 0;
+        while x < width {
+            let len = rng.range_usize(1, 3);
+            if x + len <= width && rng.chance(1, 4) {
+                write!(
+                    out,
+                    "{}",
+                    rng.range(10_u64.pow(len as u32 - 1), 10_u64.pow(len as u32) - 1)
+                )
+                .unwrap();
+                x += len;
+            }
+            // whatever follows a number must not be a digit.
+            if x < width {
+                out.push(if rng.chance(1, 8) {
+                    *rng.choose(&SYMBOLS)
+                } else {
+                    '.'
+                });
+                x += 1;
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+pub fn scratchcards(rng: &mut Rng, size: usize) -> String {
+    // copies add up quickly, cards with many copies win nothing so that part two fits a `u32`.
+    let max_copies = (u64::from(u32::MAX) / size as u64).saturating_sub(1) / 10;
+    let mut copies = vec![1_u64; size];
+    let mut out = String::new();
+    for card in 0..size {
+        let mut numbers: Vec<u64> = (1..100).collect();
+        rng.shuffle(&mut numbers);
+        let remaining = size - card - 1;
+        // mostly few matches, as in the real inputs.
+        let most = rng.range_usize(0, 10);
+        let matches = if copies[card] > max_copies {
+            0
+        } else {
+            rng.range_usize(0, most).min(remaining)
+        };
+        let current = copies[card];
+        for next in &mut copies[card + 1..=card + matches] {
+            *next += current;
+        }
+
+        let winning = &numbers[..10];
+        let mut own: Vec<u64> = winning[..matches].to_vec();
+        own.extend_from_slice(&numbers[10..35 - matches]);
+        rng.shuffle(&mut own);
+        writeln!(
+            out,
+            "Card {:>3}: {} | {}",
+            card + 1,
+            join(winning.iter().map(|n| format!("{n:>2}")), " "),
+            join(own.iter().map(|n| format!("{n:>2}")), " ")
+        )
+        .unwrap();
+    }
+    out
+}
+
+pub fn almanac(rng: &mut Rng, size: usize) -> String {
+    const CATEGORIES: [&str; 8] = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+    let max = u64::from(u32::MAX);
+
+    let seeds: Vec<String> = (0..size.clamp(1, 10))
+        .map(|_| {
+            let start = rng.range(0, max - 1);
+            let length = rng.range(1, (max - start).min(max / 20));
+            format!("{start} {length}")
+        })
+        .collect();
+    let mut out = format!("seeds: {}\n", seeds.join(" "));
+
+    for categories in CATEGORIES.windows(2) {
+        // every map is a permutation of consecutive slices of `offset..max`.
+        let offset = rng.range(0, max / 4);
+        let mut cuts: Vec<u64> = (0..size).map(|_| rng.range(offset + 1, max - 1)).collect();
+        cuts.extend([offset, max]);
+        cuts.sort_unstable();
+        cuts.dedup();
+        let sources: Vec<(u64, u64)> = cuts.windows(2).map(|w| (w[0], w[1] - w[0])).collect();
+        let mut destinations = sources.clone();
+        rng.shuffle(&mut destinations);
+        // slices are laid out in the shuffled order on the destination side.
+        let mut laid_out = Vec::new();
+        let mut destination = offset;
+        for &(source, length) in &destinations {
+            laid_out.push((destination, source, length));
+            destination += length;
+        }
+        rng.shuffle(&mut laid_out);
+
+        writeln!(out, "\n{}-to-{} map:", categories[0], categories[1]).unwrap();
+        for (destination, source, length) in laid_out {
+            writeln!(out, "{destination} {source} {length}").unwrap();
+        }
+    }
+    out
+}
+
+/// Part two concatenates the races into a single `u64` one, so there are at most four of them
+/// whatever the size.
+pub fn races(rng: &mut Rng, size: usize) -> String {
+    let (times, distances): (Vec<u64>, Vec<u64>) = (0..size.clamp(1, 4))
+        .map(|_| {
+            let time = rng.range(10, 99);
+            // strictly below the best distance, so that every race (and the concatenated one) can be won.
+            (time, rng.range(1, time * time / 4 - 1))
+        })
+        .unzip();
+    let column = |values: &[u64]| join(values.iter().map(|v| format!("{v:>5}")), " ");
+    format!(
+        "Time:     {}\nDistance: {}\n",
+        column(&times),
+        column(&distances)
+    )
+}
+
+pub fn camel_cards(rng: &mut Rng, size: usize) -> String {
+    const CARDS: [char; 13] = [
+        '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
+    ];
+    let mut hands = HashSet::new();
+    let mut out = String::new();
+    // there are 13^5 different hands, leave some room for the rejection sampling.
+    while hands.len() < size.min(300_000) {
+        // draw from a few cards only, to get interesting hand types.
+        let mut deck = CARDS.to_vec();
+        rng.shuffle(&mut deck);
+        deck.truncate(rng.range_usize(1, 5));
+        let hand: String = (0..5).map(|_| *rng.choose(&deck)).collect();
+        if hands.insert(hand.clone()) {
+            writeln!(out, "{hand} {}", rng.range(1, 1000)).unwrap();
+        }
+    }
+    out
+}
+
+/// Every ghost walks a single cycle whose length is a multiple of the instructions length,
+/// so that the answer of part two is the least common multiple of the cycles, as in the real inputs.
+pub fn network(rng: &mut Rng, size: usize) -> String {
+    const PRIMES: [usize; 20] = [
+        2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71,
+    ];
+    // 3-letter names that do not end with `A` or `Z`, see `name`.
+    const MAX_NODES: usize = 36 * 36 * 24;
+
+    let instructions: Vec<char> = (0..size.clamp(2, 400))
+        .map(|_| if rng.bool() { 'L' } else { 'R' })
+        .collect();
+    let ghosts = (1 + size / 16).min(6);
+    let max_prime = MAX_NODES / (instructions.len() * ghosts);
+    let mut primes: Vec<usize> = PRIMES.iter().copied().filter(|&p| p <= max_prime).collect();
+    rng.shuffle(&mut primes);
+    primes.truncate(ghosts);
+
+    let name = |index: usize| {
+        const FIRST: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        const LAST: &[u8] = b"BCDEFGHIJKLMNOPQRSTUVWXY";
+        [
+            FIRST[index / (36 * 24)],
+            FIRST[index / 24 % 36],
+            LAST[index % 24],
+        ]
+        .iter()
+        .map(|&b| char::from(b))
+        .collect::<String>()
+    };
+    let mut nodes = Vec::new();
+    let mut next_index = 0;
+    for (ghost, &prime) in primes.iter().enumerate() {
+        let length = prime * instructions.len();
+        let mut chain = vec![if ghost == 0 {
+            "AAA".to_string()
+        } else {
+            format!("{ghost}{ghost}A")
+        }];
+        for _ in 1..length {
+            chain.push(name(next_index));
+            next_index += 1;
+        }
+        chain.push(if ghost == 0 {
+            "ZZZ".to_string()
+        } else {
+            format!("{ghost}{ghost}Z")
+        });
+
+        // the n-th node is always left with the n-th instruction, the other side is a dead end.
+        for step in 0..=length {
+            let next = if step == length {
+                &chain[1]
+            } else {
+                &chain[step + 1]
+            };
+            let other = &chain[rng.range_usize(1, length)];
+            let (left, right) = match instructions[step % instructions.len()] {
+                'L' => (next, other),
+                _ => (other, next),
+            };
+            nodes.push(format!("{} = ({left}, {right})", chain[step]));
+        }
+    }
+    rng.shuffle(&mut nodes);
+
+    let instructions: String = instructions.into_iter().collect();
+    format!("{instructions}\n\n{}\n", nodes.join("\n"))
+}
+
+pub fn oasis_report(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        // start from the first value of every difference level, down to a constant one.
+        let degree = rng.range_usize(0, 6);
+        let mut levels: Vec<i64> = (0..=degree).map(|_| rng.range(0, 40) as i64 - 20).collect();
+        let mut values = Vec::new();
+        for _ in 0..21 {
+            values.push(levels[0]);
+            for level in 0..degree {
+                levels[level] += levels[level + 1];
+            }
+        }
+        writeln!(out, "{}", join(values, " ")).unwrap();
+    }
+    out
+}
+
+/// The loop is the boundary of a random column-convex polyomino, drawn on the corners of its cells.
+pub fn pipe_maze(rng: &mut Rng, size: usize) -> String {
+    const JUNK: [char; 7] = ['|', '-', 'L', 'J', '7', 'F', '.'];
+    let side = size.max(4);
+    let blocks = side - 1;
+
+    // column-convex: consecutive columns overlap by at least one block, so there is no hole.
+    let first = rng.range_usize(0, blocks / 2);
+    let last = rng.range_usize(first, blocks - 1);
+    let mut cells = HashSet::new();
+    let mut top = rng.range_usize(0, blocks - 1);
+    let mut bottom = rng.range_usize(top, blocks - 1);
+    for x in first..=last {
+        cells.extend((top..=bottom).map(|y| (x, y)));
+        let next_top = rng.range_usize(0, bottom);
+        let next_bottom = rng.range_usize(next_top.max(top), blocks - 1);
+        (top, bottom) = (next_top, next_bottom);
+    }
+
+    // the boundary, clockwise: every corner has a single outgoing edge.
+    let mut next = std::collections::HashMap::new();
+    for &(x, y) in &cells {
+        let filled = |dx: isize, dy: isize| {
+            cells.contains(&(x.wrapping_add_signed(dx), y.wrapping_add_signed(dy)))
+        };
+        if !filled(0, -1) {
+            next.insert((x, y), (x + 1, y));
+        }
+        if !filled(1, 0) {
+            next.insert((x + 1, y), (x + 1, y + 1));
+        }
+        if !filled(0, 1) {
+            next.insert((x + 1, y + 1), (x, y + 1));
+        }
+        if !filled(-1, 0) {
+            next.insert((x, y + 1), (x, y));
+        }
+    }
+    let &start = next.keys().min().unwrap();
+    let mut path = vec![start];
+    while next[path.last().unwrap()] != start {
+        path.push(next[path.last().unwrap()]);
+    }
+
+    let mut map: Vec<Vec<char>> = (0..side)
+        .map(|_| (0..side).map(|_| *rng.choose(&JUNK)).collect())
+        .collect();
+    for (i, &(x, y)) in path.iter().enumerate() {
+        let (before, after) = (
+            path[(i + path.len() - 1) % path.len()],
+            path[(i + 1) % path.len()],
+        );
+        let towards = |(ox, oy): (usize, usize)| match (ox.cmp(&x), oy.cmp(&y)) {
+            (_, std::cmp::Ordering::Less) => 'N',
+            (_, std::cmp::Ordering::Greater) => 'S',
+            (std::cmp::Ordering::Less, _) => 'W',
+            _ => 'E',
+        };
+        let mut ends = [towards(before), towards(after)];
+        ends.sort_unstable();
+        map[y][x] = match ends {
+            ['N', 'S'] => '|',
+            ['E', 'W'] => '-',
+            ['E', 'N'] => 'L',
+            ['N', 'W'] => 'J',
+            ['S', 'W'] => '7',
+            ['E', 'S'] => 'F',
+            _ => unreachable!(),
+        };
+    }
+
+    // the start's shape is deduced from its neighbours, only loop pipes may connect to it.
+    let loop_cells: HashSet<(usize, usize)> = path.iter().copied().collect();
+    let (sx, sy) = *rng.choose(&path);
+    map[sy][sx] = 'S';
+    for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+        let (x, y) = (sx.wrapping_add_signed(dx), sy.wrapping_add_signed(dy));
+        if x < side && y < side && !loop_cells.contains(&(x, y)) {
+            map[y][x] = '.';
+        }
+    }
+
+    map.into_iter()
+        .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+        .collect()
+}
+
+pub fn galaxy_image(rng: &mut Rng, size: usize) -> String {
+    let side = size.max(2);
+    let mut image = grid(
+        rng,
+        side,
+        side,
+        |rng| if rng.chance(1, 12) { '#' } else { '.' },
+    );
+    // at least a pair of galaxies, on the first and last lines.
+    image.replace_range(0..1, "#");
+    let last = image.len() - 2;
+    image.replace_range(last..=last, "#");
+    image
+}
+
+/// Counts the arrangements of a record, used to keep part two within a `usize`.
+fn arrangements(springs: &[u8], groups: &[usize]) -> u128 {
+    // ways[g][i]: arrangements of the first `i` springs matching the first `g` groups.
+    let mut ways = vec![vec![0_u128; springs.len() + 1]; groups.len() + 1];
+    ways[0][0] = 1;
+    for i in 1..=springs.len() {
+        if springs[i - 1] != b'#' {
+            ways[0][i] = ways[0][i - 1];
+        }
+    }
+    for (g, &group) in groups.iter().enumerate() {
+        for i in 1..=springs.len() {
+            let mut count = if springs[i - 1] == b'#' {
+                0
+            } else {
+                ways[g + 1][i - 1]
+            };
+            if i >= group && springs[i - group..i].iter().all(|&s| s != b'.') {
+                if i == group {
+                    count += ways[g][0];
+                } else if springs[i - group - 1] != b'#' {
+                    count += ways[g][i - group - 1];
+                }
+            }
+            ways[g + 1][i] = count;
+        }
+    }
+    ways[groups.len()][springs.len()]
+}
+
+pub fn condition_records(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    let mut lines = 0;
+    while lines < size {
+        let len = rng.range_usize(4, 20);
+        let springs = record_springs(rng, len);
+        let line = record_line(&springs);
+        let (pattern, _) = line.split_once(' ').unwrap();
+
+        let mut unfolded = pattern.as_bytes().to_vec();
+        for _ in 0..4 {
+            unfolded.push(b'?');
+            unfolded.extend_from_slice(pattern.as_bytes());
+        }
+        if arrangements(&unfolded, &record_groups(&springs).repeat(5)) > 1 << 40 {
+            continue;
+        }
+        writeln!(out, "{line}").unwrap();
+        lines += 1;
+    }
+    out
+}
+
+/// Pattern with a reflection across `split` columns (or rows when transposed),
+/// returns the number of differences for every possible split.
+fn reflection_differences(pattern: &[Vec<bool>]) -> Vec<usize> {
+    let width = pattern[0].len();
+    (1..width)
+        .map(|split| {
+            pattern
+                .iter()
+                .map(|row| {
+                    (0..split.min(width - split))
+                        .filter(|&d| row[split - 1 - d] != row[split + d])
+                        .count()
+                })
+                .sum()
+        })
+        .collect()
+}
+
+fn transpose(pattern: &[Vec<bool>]) -> Vec<Vec<bool>> {
+    (0..pattern[0].len())
+        .map(|x| pattern.iter().map(|row| row[x]).collect())
+        .collect()
+}
+
+/// Every pattern has a single perfect reflection across columns,
+/// and a single reflection across rows with exactly one smudge.
+pub fn mirror_patterns(rng: &mut Rng, size: usize) -> String {
+    let mut patterns = Vec::new();
+    while patterns.len() < size {
+        let (width, height) = (rng.range_usize(5, 17), rng.range_usize(5, 17));
+        let column_split = rng.range_usize(1, width - 1);
+        let row_split = rng.range_usize(1, height - 1);
+        if 2 * column_split == width {
+            continue;
+        }
+
+        // symmetric across both lines: cells mirroring each other get the same value.
+        let mirror = |split: usize, len: usize, i: usize| {
+            let reflected = (2 * split).checked_sub(i + 1)?;
+            (reflected < len).then_some(reflected)
+        };
+        let mut pattern = vec![vec![false; width]; height];
+        for y in 0..height {
+            for x in 0..width {
+                let mirrored = [
+                    mirror(column_split, width, x).map(|mx| (mx, y)),
+                    mirror(row_split, height, y).map(|my| (x, my)),
+                    mirror(column_split, width, x).zip(mirror(row_split, height, y)),
+                ];
+                let earlier = mirrored
+                    .into_iter()
+                    .flatten()
+                    .find(|&(mx, my)| (my, mx) < (y, x));
+                pattern[y][x] = match earlier {
+                    Some((mx, my)) => pattern[my][mx],
+                    None => rng.chance(2, 5),
+                };
+            }
+        }
+
+        // the smudge is in a column that is not reflected, which keeps the column reflection perfect.
+        let smudge_x = if 2 * column_split < width {
+            rng.range_usize(2 * column_split, width - 1)
+        } else {
+            rng.range_usize(0, 2 * column_split - width - 1)
+        };
+        let smudge_y = rng.range_usize(
+            (2 * row_split).saturating_sub(height),
+            (2 * row_split).min(height) - 1,
+        );
+        pattern[smudge_y][smudge_x] = !pattern[smudge_y][smudge_x];
+
+        let columns = reflection_differences(&pattern);
+        let rows = reflection_differences(&transpose(&pattern));
+        let count = |differences: usize| {
+            columns
+                .iter()
+                .chain(&rows)
+                .filter(|&&d| d == differences)
+                .count()
+        };
+        if count(0) == 1 && count(1) == 1 && columns[column_split - 1] == 0 {
+            patterns.push(
+                pattern
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|&r| if r { '#' } else { '.' })
+                            .collect::<String>()
+                            + "\n"
+                    })
+                    .collect::<String>(),
+            );
+        }
+    }
+    patterns.join("\n")
+}
+
+pub fn rock_platform(rng: &mut Rng, size: usize) -> String {
+    let side = size.max(2);
+    grid(rng, side, side, |rng| match rng.range(0, 11) {
+        0..=2 => 'O',
+        3 | 4 => '#',
+        _ => '.',
+    })
+}
+
+pub fn mirror_contraption(rng: &mut Rng, size: usize) -> String {
+    const DEVICES: [char; 4] = ['|', '-', '/', '\\'];
+    let side = size.max(2);
+    grid(rng, side, side, |rng| {
+        if rng.chance(1, 8) {
+            *rng.choose(&DEVICES)
+        } else {
+            '.'
+        }
+    })
+}
+
+pub fn city_blocks(rng: &mut Rng, size: usize) -> String {
+    let side = size.max(2);
+    grid(rng, side, side, |rng| {
+        char::from(b'1' + rng.range(0, 8) as u8)
+    })
+}
+
+/// The colors hide a second, much larger, polygon with the same number of instructions.
+pub fn dig_plan(rng: &mut Rng, size: usize) -> String {
+    let small = polygon_outline(&polygon_columns(rng, size, 10));
+    // lengths are encoded with 5 hexadecimal digits.
+    let large = polygon_outline(&polygon_columns(rng, size, 1 << 18));
+    let mut out = String::new();
+    for ((direction, length), (color_direction, color_length)) in small.into_iter().zip(large) {
+        let digit = match color_direction {
+            'R' => 0,
+            'D' => 1,
+            'L' => 2,
+            _ => 3,
+        };
+        writeln!(out, "{direction} {length} (#{color_length:05x}{digit})").unwrap();
+    }
+    out
+}
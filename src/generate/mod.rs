@@ -0,0 +1,107 @@
+/// Synthetic puzzle inputs, used to see how solutions scale beyond the example and the real input.
+///
+/// Every generator produces a valid input (i.e. one that satisfies the guarantees of the puzzle text)
+/// whose size grows with `size`: the number of lines for list-like inputs, the side of the grid for
+/// grid-like inputs. The same `(day, size, seed)` always yields the same input.
+use std::fs;
+use std::path::PathBuf;
+
+use crate::random::Rng;
+use crate::Day;
+
+mod days;
+pub mod shapes;
+
+/// Generates an input for `day`, returns [`None`] if there is no generator for that day.
+#[must_use]
+pub fn generate(day: Day, size: usize, seed: u64) -> Option<String> {
+    let generator = generator(day)?;
+    let mut rng = Rng::new(seed ^ (u64::from(day.into_inner()) << 56));
+    Some(generator(&mut rng, size.max(1)))
+}
+
+/// Days that have a generator.
+pub fn available_days() -> impl Iterator<Item = Day> {
+    crate::all_days().filter(|&day| generator(day).is_some())
+}
+
+type Generator = fn(&mut Rng, usize) -> String;
+
+fn generator(day: Day) -> Option<Generator> {
+    let generator: Generator = match day.into_inner() {
+        1 => days::calibration_document,
+        2 => days::cube_games,
+        3 => days::engine_schematic,
+        4 => days::scratchcards,
+        5 => days::almanac,
+        6 => days::races,
+        7 => days::camel_cards,
+        8 => days::network,
+        9 => days::oasis_report,
+        10 => days::pipe_maze,
+        11 => days::galaxy_image,
+        12 => days::condition_records,
+        13 => days::mirror_patterns,
+        14 => days::rock_platform,
+        16 => days::mirror_contraption,
+        17 => days::city_blocks,
+        18 => days::dig_plan,
+        _ => return None,
+    };
+    Some(generator)
+}
+
+/// Path of a generated input in the scratch directory (`data/generated`, not tracked by git).
+#[must_use]
+pub fn get_path(day: Day, size: usize, seed: u64) -> PathBuf {
+    PathBuf::from("data")
+        .join("generated")
+        .join(format!("{day}-n{size}-s{seed}.txt"))
+}
+
+/// Generates an input and writes it to the scratch directory, returns the path of the written file.
+pub fn write(day: Day, size: usize, seed: u64) -> Result<PathBuf, std::io::Error> {
+    let content = generate(day, size, seed).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("no generator for day {day}"),
+        )
+    })?;
+    let path = get_path(day, size, seed);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, content)?;
+    Ok(path)
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{available_days, generate};
+    use crate::day;
+
+    #[test]
+    fn is_deterministic() {
+        for day in available_days() {
+            assert_eq!(generate(day, 8, 1), generate(day, 8, 1));
+        }
+        assert_ne!(generate(day!(17), 8, 1), generate(day!(17), 8, 2));
+    }
+
+    #[test]
+    fn grows_with_size() {
+        // day 6 is bounded by part two, see `days::races`.
+        for day in available_days().filter(|&day| day != day!(6)) {
+            let small = generate(day, 5, 3).unwrap();
+            let large = generate(day, 50, 3).unwrap();
+            assert!(small.len() < large.len(), "day {day} does not grow");
+            assert!(large.ends_with('\n'));
+        }
+    }
+
+    #[test]
+    fn unknown_days() {
+        assert!(generate(day!(15), 10, 0).is_none());
+        assert!(generate(day!(25), 10, 0).is_none());
+    }
+}
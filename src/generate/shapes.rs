@@ -0,0 +1,106 @@
+/// Building blocks shared by the generators and the property tests of the solutions, so that both
+/// draw their inputs from the same shapes.
+use crate::random::Rng;
+
+/// Columns `(width, top, bottom)` of a clockwise x-monotone polygon, see [`polygon_outline`].
+/// Every column has a width up to `max`, consecutive columns overlap by at least a line and never
+/// share a top or a bottom, so that no vertical instruction of the outline is empty.
+pub fn polygon_columns(rng: &mut Rng, count: usize, max: u64) -> Vec<(u64, i64, i64)> {
+    let max = max as i64;
+    let mut pick = |low: i64, high: i64| low + rng.range(0, high.abs_diff(low)) as i64;
+    let mut columns = Vec::new();
+    let (mut top, mut bottom) = (0, pick(1, max));
+    for _ in 0..count {
+        columns.push((pick(1, max) as u64, top, bottom));
+        let mut next_top = top;
+        while next_top == top {
+            next_top = pick(top - max, bottom - 1);
+        }
+        let overlap_top = top.max(next_top);
+        let mut next_bottom = bottom;
+        while next_bottom == bottom {
+            next_bottom = pick(overlap_top + 1, overlap_top + 1 + max);
+        }
+        (top, bottom) = (next_top, next_bottom);
+    }
+    columns
+}
+
+/// Outline of a polygon made of columns, as `(direction, length)` instructions going clockwise
+/// from the top left corner. Vertical instructions between columns sharing a top or a bottom are
+/// left out.
+///
+/// # Panics
+///
+/// If `columns` is empty.
+#[must_use]
+pub fn polygon_outline(columns: &[(u64, i64, i64)]) -> Vec<(char, u64)> {
+    let mut instructions = Vec::new();
+    let vertical = |instructions: &mut Vec<(char, u64)>, from: i64, to: i64| {
+        if to != from {
+            instructions.push((if to < from { 'U' } else { 'D' }, from.abs_diff(to)));
+        }
+    };
+    for (i, &(width, top, _)) in columns.iter().enumerate() {
+        instructions.push(('R', width));
+        if let Some(&(_, next_top, _)) = columns.get(i + 1) {
+            vertical(&mut instructions, top, next_top);
+        }
+    }
+    let &(_, last_top, last_bottom) = columns.last().unwrap();
+    vertical(&mut instructions, last_top, last_bottom);
+    for (i, &(width, _, bottom)) in columns.iter().enumerate().rev() {
+        instructions.push(('L', width));
+        if i > 0 {
+            vertical(&mut instructions, bottom, columns[i - 1].2);
+        }
+    }
+    let &(_, first_top, first_bottom) = columns.first().unwrap();
+    vertical(&mut instructions, first_bottom, first_top);
+    instructions
+}
+
+/// A condition record of `len` springs drawn from a known arrangement, as `(damaged, unknown)`
+/// for every spring, so that it has at least one solution. At least one spring is damaged.
+///
+/// # Panics
+///
+/// If `len` is zero.
+pub fn record_springs(rng: &mut Rng, len: usize) -> Vec<(bool, bool)> {
+    let mut springs: Vec<(bool, bool)> = (0..len)
+        .map(|_| (rng.chance(2, 5), rng.chance(2, 5)))
+        .collect();
+    if !springs.iter().any(|s| s.0) {
+        let i = rng.range_usize(0, len - 1);
+        springs[i].0 = true;
+    }
+    springs
+}
+
+/// Sizes of the groups of damaged springs of a known arrangement.
+#[must_use]
+pub fn record_groups(springs: &[(bool, bool)]) -> Vec<usize> {
+    springs
+        .split(|&(damaged, _)| !damaged)
+        .map(<[_]>::len)
+        .filter(|&len| len > 0)
+        .collect()
+}
+
+/// The record as a line of the puzzle input (without the line break), e.g. `#.?#?? 1,3`.
+#[must_use]
+pub fn record_line(springs: &[(bool, bool)]) -> String {
+    let pattern: String = springs
+        .iter()
+        .map(|&(damaged, unknown)| match (damaged, unknown) {
+            (_, true) => '?',
+            (true, false) => '#',
+            (false, false) => '.',
+        })
+        .collect();
+    let groups: Vec<String> = record_groups(springs)
+        .iter()
+        .map(usize::to_string)
+        .collect();
+    format!("{pattern} {}", groups.join(","))
+}
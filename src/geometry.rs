@@ -0,0 +1,218 @@
+/// Exact geometry of integer polygons: area with the shoelace formula, and lattice point counts
+/// with Pick's theorem (`area = interior + boundary / 2 - 1`).
+///
+/// Areas are returned doubled, as the area of a polygon with integer vertices is a multiple of 1/2.
+/// All of it holds whatever the orientation of the vertices.
+use num::Integer;
+
+use crate::point::{Direction, IPoint};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// As seen on screen, with `y` growing downwards.
+    Clockwise,
+    Counterclockwise,
+}
+
+/// A simple polygon, closed by an edge from its last vertex back to its first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon {
+    vertices: Vec<IPoint>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<IPoint>) -> Self {
+        Self { vertices }
+    }
+
+    /// The polygon drawn by walking from `start`, like a dig plan.
+    /// A walk that ends back on `start` does not repeat it.
+    pub fn from_walk(start: IPoint, moves: impl IntoIterator<Item = (Direction, usize)>) -> Self {
+        let mut vertices = vec![start];
+        let mut current = start;
+        for (dir, n) in moves {
+            current = current.step_n(dir, n);
+            vertices.push(current);
+        }
+        if vertices.len() > 1 && vertices.last() == Some(&start) {
+            vertices.pop();
+        }
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[IPoint] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (IPoint, IPoint)> + '_ {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(&a, &b)| (a, b))
+    }
+
+    /// Twice the area, positive for clockwise polygons, see [`Orientation`].
+    pub fn twice_signed_area(&self) -> i128 {
+        self.edges()
+            .map(|(a, b)| a.x as i128 * b.y as i128 - b.x as i128 * a.y as i128)
+            .sum()
+    }
+
+    /// Twice the area.
+    pub fn twice_area(&self) -> u128 {
+        self.twice_signed_area().unsigned_abs()
+    }
+
+    /// [`None`] for degenerate polygons, of zero area.
+    pub fn orientation(&self) -> Option<Orientation> {
+        match self.twice_signed_area() {
+            0 => None,
+            a if a > 0 => Some(Orientation::Clockwise),
+            _ => Some(Orientation::Counterclockwise),
+        }
+    }
+
+    /// Lattice points on the edges, vertices included.
+    pub fn boundary_points(&self) -> u128 {
+        self.edges()
+            .map(|(a, b)| {
+                let (dx, dy) = (a.x.abs_diff(b.x) as u128, a.y.abs_diff(b.y) as u128);
+                dx.gcd(&dy)
+            })
+            .sum()
+    }
+
+    /// Lattice points strictly inside, by Pick's theorem. [`None`] for degenerate polygons, of
+    /// zero area, and for self-intersecting ones that leave fewer than none, as the theorem
+    /// doesn't hold for them.
+    pub fn interior_points(&self) -> Option<u128> {
+        let twice_area = self.twice_area();
+        if twice_area == 0 {
+            return None;
+        }
+        // 2A = 2I + B - 2
+        Some((twice_area + 2).checked_sub(self.boundary_points())? / 2)
+    }
+
+    /// Lattice points inside or on the boundary. For a rectilinear polygon drawn through the
+    /// centers of grid cells, that's the number of cells it covers, outline included.
+    /// [`None`] when [`Polygon::interior_points`] is.
+    pub fn lattice_points(&self) -> Option<u128> {
+        Some(self.interior_points()? + self.boundary_points())
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Orientation, Polygon};
+    use crate::point::{Direction, IPoint};
+    use crate::random::Rng;
+
+    fn polygon(vertices: &[(isize, isize)]) -> Polygon {
+        Polygon::new(vertices.iter().map(|&v| IPoint::from(v)).collect())
+    }
+
+    fn reversed(polygon: &Polygon) -> Polygon {
+        Polygon::new(polygon.vertices().iter().rev().copied().collect())
+    }
+
+    #[test]
+    fn measures_squares_in_both_orientations() {
+        // 3x3 square: 16 lattice points, 12 on the boundary.
+        let square = polygon(&[(0, 0), (3, 0), (3, 3), (0, 3)]);
+        for p in [reversed(&square), square.clone()] {
+            assert_eq!(p.twice_area(), 18);
+            assert_eq!(p.boundary_points(), 12);
+            assert_eq!(p.interior_points(), Some(4));
+            assert_eq!(p.lattice_points(), Some(16));
+        }
+        assert_eq!(square.orientation(), Some(Orientation::Clockwise));
+        assert_eq!(
+            reversed(&square).orientation(),
+            Some(Orientation::Counterclockwise)
+        );
+        assert_eq!(polygon(&[(0, 0), (2, 2)]).orientation(), None);
+    }
+
+    #[test]
+    fn walks_dig_plans() {
+        // an L shape of 3 + 2 cells wide.
+        let moves = [
+            (Direction::Right, 2),
+            (Direction::Down, 1),
+            (Direction::Left, 1),
+            (Direction::Down, 1),
+            (Direction::Left, 1),
+            (Direction::Up, 2),
+        ];
+        let walk = Polygon::from_walk(IPoint::new(-5, 7), moves);
+        assert_eq!(walk.vertices().len(), 6);
+        assert_eq!(walk.lattice_points(), Some(8));
+        assert_eq!(walk.interior_points(), Some(0));
+        assert_eq!(walk.twice_area(), 6);
+    }
+
+    #[test]
+    fn counts_triangles_like_brute_force() {
+        let mut rng = Rng::new(37);
+        for _ in 0..500 {
+            let mut point =
+                || IPoint::new(rng.range(0, 12) as isize - 6, rng.range(0, 12) as isize - 6);
+            let (a, b, c) = (point(), point(), point());
+            let triangle = Polygon::new(vec![a, b, c]);
+            if triangle.orientation().is_none() {
+                continue;
+            }
+            // sign of the cross product of `u - v` and `w - v`.
+            let side = |u: IPoint, v: IPoint, w: IPoint| {
+                ((u.x - v.x) * (w.y - v.y) - (u.y - v.y) * (w.x - v.x)).signum()
+            };
+            let (mut inside, mut on_edge) = (0, 0);
+            for x in -6..=6 {
+                for y in -6..=6 {
+                    let p = IPoint::new(x, y);
+                    let sides = [side(b, a, p), side(c, b, p), side(a, c, p)];
+                    if sides.iter().all(|&s| s >= 0) || sides.iter().all(|&s| s <= 0) {
+                        if sides.contains(&0) {
+                            on_edge += 1;
+                        } else {
+                            inside += 1;
+                        }
+                    }
+                }
+            }
+            assert_eq!(triangle.interior_points(), Some(inside), "{triangle:?}");
+            assert_eq!(triangle.boundary_points(), on_edge, "{triangle:?}");
+            assert_eq!(reversed(&triangle).lattice_points(), Some(inside + on_edge));
+        }
+    }
+
+    #[test]
+    fn handles_huge_coordinates() {
+        let big = 1 << 40;
+        let square = polygon(&[(-big, -big), (big, -big), (big, big), (-big, big)]);
+        let side = 2 * big as u128;
+        assert_eq!(square.twice_area(), 2 * side * side);
+        assert_eq!(square.lattice_points(), Some((side + 1) * (side + 1)));
+    }
+
+    #[test]
+    fn rejects_degenerate_polygons() {
+        let there_and_back = Polygon::from_walk(
+            IPoint::default(),
+            [(Direction::Right, 5), (Direction::Left, 5)],
+        );
+        assert_eq!(there_and_back.boundary_points(), 10);
+        assert_eq!(there_and_back.interior_points(), None);
+        assert_eq!(there_and_back.lattice_points(), None);
+        assert_eq!(Polygon::new(Vec::new()).interior_points(), None);
+        // a bow tie, whose halves cancel out.
+        let bow_tie = polygon(&[(0, 0), (4, 4), (4, 0), (0, 4)]);
+        assert_eq!(bow_tie.interior_points(), None);
+        // a unit square at the end of a trench walked both ways: more boundary points than
+        // Pick's theorem allows for its area.
+        let folded = polygon(&[(0, 0), (10, 0), (10, 1), (9, 1), (9, 0)]);
+        assert_eq!(folded.twice_area(), 2);
+        assert_eq!(folded.lattice_points(), None);
+    }
+}
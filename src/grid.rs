@@ -0,0 +1,410 @@
+/// Two-dimensional grid with flat storage, for the many "map" puzzles.
+///
+/// Positions are `(x, y)` tuples: `x` is the column, `y` the line, `(0, 0)` is the top left corner.
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::ops::{Index, IndexMut};
+
+pub type Pos = (usize, usize);
+
+/// Offsets of the 4 orthogonal neighbours: up, right, down, left.
+pub const NEIGHBOURS_4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Offsets of the 8 neighbours, clockwise from the top left one.
+pub const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseGridError {
+    Empty,
+    /// A line does not have the same length as the first one. Lines are numbered from 1.
+    Ragged {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The mapping closure rejected a char. Lines and columns are numbered from 1.
+    InvalidChar {
+        line: usize,
+        column: usize,
+        char: char,
+    },
+}
+
+impl Error for ParseGridError {}
+
+impl Display for ParseGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty grid"),
+            Self::Ragged {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line} has {found} cells, expected {expected} like the first line"
+            ),
+            Self::InvalidChar { line, column, char } => {
+                write!(f, "unexpected {char:?} at line {line}, column {column}")
+            }
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid filled with `value`.
+    ///
+    /// # Panics
+    /// If `width` or `height` is zero: like parsed grids, grids are never empty.
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        assert!(width > 0 && height > 0, "empty {width}x{height} grid");
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Creates a grid with the value returned by `f` for every position, line by line.
+    ///
+    /// # Panics
+    /// If `width` or `height` is zero, see [`Grid::new`].
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Pos) -> T) -> Self {
+        assert!(width > 0 && height > 0, "empty {width}x{height} grid");
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(&mut f)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parses one cell per char, one row per line.
+    pub fn parse(input: &str, mut f: impl FnMut(char) -> T) -> Result<Self, ParseGridError> {
+        Self::try_parse(input, |c| Some(f(c)))
+    }
+
+    /// Parses one cell per char, one row per line. `f` returns [`None`] for invalid chars.
+    pub fn try_parse(
+        input: &str,
+        mut f: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseGridError> {
+        let mut cells = Vec::with_capacity(input.len());
+        let mut width = None;
+        let mut height = 0;
+        for (y, line) in input.lines().enumerate() {
+            let mut found = 0;
+            for (x, c) in line.chars().enumerate() {
+                cells.push(f(c).ok_or(ParseGridError::InvalidChar {
+                    line: y + 1,
+                    column: x + 1,
+                    char: c,
+                })?);
+                found += 1;
+            }
+            match width {
+                None => width = Some(found),
+                Some(expected) if expected != found => {
+                    return Err(ParseGridError::Ragged {
+                        line: y + 1,
+                        expected,
+                        found,
+                    })
+                }
+                Some(_) => (),
+            }
+            height += 1;
+        }
+        match width {
+            None | Some(0) => Err(ParseGridError::Empty),
+            Some(width) => Ok(Self {
+                width,
+                height,
+                cells,
+            }),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Position of the bottom right corner.
+    pub fn max_pos(&self) -> Pos {
+        (self.width - 1, self.height - 1)
+    }
+
+    pub fn in_bounds(&self, pos: Pos) -> bool {
+        pos.0 < self.width && pos.1 < self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.in_bounds(pos).then(|| &self.cells[self.index(pos)])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.in_bounds(pos) {
+            let index = self.index(pos);
+            Some(&mut self.cells[index])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the cell at `pos`, wrapping an `x` past the width onto the next lines:
+    /// `(width, y)` is `(0, y + 1)`. A position past the last line still panics.
+    pub fn get_wrapping(&self, pos: Pos) -> &T {
+        &self.cells[pos.1 * self.width + pos.0]
+    }
+
+    pub fn swap(&mut self, a: Pos, b: Pos) {
+        let (a, b) = (self.index(a), self.index(b));
+        self.cells.swap(a, b);
+    }
+
+    /// Moves `pos` by `delta`, returns [`None`] if it leaves the grid.
+    pub fn offset(&self, pos: Pos, delta: (isize, isize)) -> Option<Pos> {
+        let moved = (
+            pos.0.checked_add_signed(delta.0)?,
+            pos.1.checked_add_signed(delta.1)?,
+        );
+        self.in_bounds(moved).then_some(moved)
+    }
+
+    /// The up to 4 orthogonal neighbours of `pos` within the grid.
+    pub fn neighbours_4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBOURS_4
+            .iter()
+            .filter_map(move |&delta| self.offset(pos, delta))
+    }
+
+    /// The up to 8 neighbours of `pos` within the grid, diagonals included.
+    pub fn neighbours_8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBOURS_8
+            .iter()
+            .filter_map(move |&delta| self.offset(pos, delta))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        self.cells.chunks_exact(self.width)
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        assert!(
+            x < self.width,
+            "column {x} out of a grid of width {}",
+            self.width
+        );
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl DoubleEndedIterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// All positions, line by line.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// All cells, line by line.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// All cells with their position, line by line.
+    pub fn enumerate(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// Position of the first cell matching `predicate`, line by line.
+    pub fn position(&self, predicate: impl FnMut(&T) -> bool) -> Option<Pos> {
+        let index = self.cells.iter().position(predicate)?;
+        Some((index % self.width, index / self.width))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    fn index(&self, pos: Pos) -> usize {
+        assert!(
+            self.in_bounds(pos),
+            "position {pos:?} out of a {}x{} grid",
+            self.width,
+            self.height
+        );
+        pos.1 * self.width + pos.0
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &Self::Output {
+        &self.cells[Grid::index(self, pos)]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut Self::Output {
+        let index = Grid::index(self, pos);
+        &mut self.cells[index]
+    }
+}
+
+/// Renders the grid as in the puzzle input, every cell being displayed without separator.
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders the dimensions then every line, cells being separated by a space.
+impl<T: Debug> Debug for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Grid {}x{}", self.width, self.height)?;
+        for row in self.rows() {
+            let cells: Vec<String> = row.iter().map(|cell| format!("{cell:?}")).collect();
+            writeln!(f, "{}", cells.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Grid, ParseGridError};
+
+    fn example() -> Grid<char> {
+        Grid::parse("abc\ndef\n", |c| c).unwrap()
+    }
+
+    #[test]
+    fn parses_and_renders() {
+        let grid = example();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.max_pos(), (2, 1));
+        assert_eq!(grid.to_string(), "abc\ndef\n");
+        assert_eq!(format!("{grid:?}"), "Grid 3x2\n'a' 'b' 'c'\n'd' 'e' 'f'\n");
+
+        let digits = Grid::parse("12\n34", |c| c.to_digit(10).unwrap()).unwrap();
+        assert_eq!(digits.iter().sum::<u32>(), 10);
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        assert_eq!(Grid::parse("", |c| c), Err(ParseGridError::Empty));
+        assert_eq!(
+            Grid::parse("ab\nabc\n", |c| c),
+            Err(ParseGridError::Ragged {
+                line: 2,
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(
+            Grid::try_parse("..\n.x\n", |c| (c == '.').then_some(())),
+            Err(ParseGridError::InvalidChar {
+                line: 2,
+                column: 2,
+                char: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn accesses_cells() {
+        let mut grid = example();
+        assert_eq!(grid.get((2, 1)), Some(&'f'));
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(*grid.get_wrapping((1, 1)), 'e');
+        grid[(0, 0)] = 'z';
+        *grid.get_mut((1, 0)).unwrap() = 'y';
+        grid.swap((2, 0), (2, 1));
+        assert_eq!(grid.to_string(), "zyf\ndec\n");
+        assert_eq!(grid.position(|&c| c == 'e'), Some((1, 1)));
+    }
+
+    #[test]
+    fn iterates_rows_and_columns() {
+        let grid = example();
+        let rows: Vec<String> = grid.rows().map(|r| r.iter().collect()).collect();
+        assert_eq!(rows, ["abc", "def"]);
+        let columns: Vec<String> = grid.columns().map(Iterator::collect).collect();
+        assert_eq!(columns, ["ad", "be", "cf"]);
+        assert_eq!(grid.column(1).rev().collect::<String>(), "eb");
+        assert_eq!(grid.row(1), ['d', 'e', 'f']);
+        assert_eq!(grid.enumerate().nth(4), Some(((1, 1), &'e')));
+    }
+
+    #[test]
+    fn iterates_neighbours() {
+        let grid = Grid::new(3, 3, 0);
+        assert_eq!(
+            grid.neighbours_4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours_4((1, 1)).count(), 4);
+        assert_eq!(
+            grid.neighbours_8((2, 0)).collect::<Vec<_>>(),
+            [(2, 1), (1, 1), (1, 0)]
+        );
+        assert_eq!(grid.neighbours_8((1, 1)).count(), 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "empty 0x3 grid")]
+    fn rejects_empty_grids() {
+        Grid::from_fn(0, 3, |_| 0);
+    }
+}
@@ -1,4 +1,19 @@
+pub mod cycle;
 mod day;
+pub mod generate;
+pub mod geometry;
+pub mod grid;
+pub mod matcher;
+pub mod math;
+pub mod memo;
+pub mod parse;
+pub mod point;
+pub mod property;
+pub mod random;
+pub mod ranges;
+pub mod record;
+pub mod render;
+pub mod search;
 pub mod template;
 
 pub use day::*;
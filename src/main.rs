@@ -1,7 +1,11 @@
-use advent_of_code::template::commands::{all, download, read, scaffold, solve};
+use advent_of_code::template::commands::{
+    all, download, gen, leaderboard, read, scaffold, scale, solve,
+};
 use args::{parse, AppArguments};
 
 mod args {
+    use std::num::ParseIntError;
+    use std::path::PathBuf;
     use std::process;
 
     use advent_of_code::Day;
@@ -26,15 +30,50 @@ mod args {
             release: bool,
             time: bool,
         },
+        Scale {
+            day: Day,
+            sizes: Option<Vec<usize>>,
+            inputs: Vec<PathBuf>,
+            seed: u64,
+            release: bool,
+        },
+        Leaderboard {
+            path: String,
+        },
+        Gen {
+            day: Day,
+            size: usize,
+            seed: u64,
+        },
     }
 
     pub fn parse() -> Result<AppArguments, Box<dyn std::error::Error>> {
         let mut args = pico_args::Arguments::from_env();
 
         let app_args = match args.subcommand()?.as_deref() {
-            Some("all") => AppArguments::All {
-                release: args.contains("--release"),
-                time: args.contains("--time"),
+            Some("all") => {
+                let release = args.contains("--release");
+                let time = args.contains("--time");
+                // `cargo time <day> --scale` expands to `all --release --time <day> --scale`.
+                if args.contains("--scale") {
+                    AppArguments::Scale {
+                        sizes: args.opt_value_from_fn("--sizes", parse_sizes)?,
+                        inputs: args.values_from_str("--input")?,
+                        seed: args.opt_value_from_str("--seed")?.unwrap_or(0),
+                        day: args.free_from_str()?,
+                        release,
+                    }
+                } else {
+                    AppArguments::All { release, time }
+                }
+            }
+            Some("gen") => AppArguments::Gen {
+                day: args.free_from_str()?,
+                size: args.opt_value_from_str("--size")?.unwrap_or(100),
+                seed: args.opt_value_from_str("--seed")?.unwrap_or(0),
+            },
+            Some("leaderboard") => AppArguments::Leaderboard {
+                path: args.free_from_str()?,
             },
             Some("download") => AppArguments::Download {
                 day: args.free_from_str()?,
@@ -68,6 +107,10 @@ mod args {
 
         Ok(app_args)
     }
+
+    fn parse_sizes(s: &str) -> Result<Vec<usize>, ParseIntError> {
+        s.split(',').map(str::parse).collect()
+    }
 }
 
 fn main() {
@@ -79,8 +122,17 @@ fn main() {
         Ok(args) => match args {
             AppArguments::All { release, time } => all::handle(release, time),
             AppArguments::Download { day } => download::handle(day),
+            AppArguments::Gen { day, size, seed } => gen::handle(day, size, seed),
+            AppArguments::Leaderboard { path } => leaderboard::handle(&path),
             AppArguments::Read { day } => read::handle(day),
             AppArguments::Scaffold { day } => scaffold::handle(day),
+            AppArguments::Scale {
+                day,
+                sizes,
+                inputs,
+                seed,
+                release,
+            } => scale::handle(day, sizes, inputs, seed, release),
             AppArguments::Solve {
                 day,
                 release,
@@ -0,0 +1,207 @@
+/// Multi-pattern string matching with an Aho-Corasick automaton.
+///
+/// The automaton reads the text one byte at a time, without ever going back, and reports every
+/// occurrence of every pattern, overlapping ones included: "eightwo" holds both "eight" and "two".
+/// Transitions are stored as a full table of 256 entries per state, so each byte costs one lookup.
+use std::collections::VecDeque;
+
+const ROOT: u32 = 0;
+const ALPHABET: usize = 256;
+
+#[derive(Debug, Clone)]
+pub struct Matcher<V> {
+    /// `next[state * ALPHABET + byte]`, the state after reading `byte`.
+    next: Vec<u32>,
+    /// Length and value of the longest pattern ending at each state.
+    outputs: Vec<Option<(usize, V)>>,
+    /// Closest state along the failure links with an output, for the shorter patterns.
+    output_links: Vec<Option<u32>>,
+}
+
+/// An occurrence of a pattern, `start..end` in the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'m, V> {
+    pub start: usize,
+    pub end: usize,
+    pub value: &'m V,
+}
+
+impl<V> Matcher<V> {
+    /// Builds the automaton of the `(pattern, value)` pairs. The first value wins for duplicate
+    /// patterns.
+    ///
+    /// # Panics
+    /// If a pattern is empty.
+    pub fn new<P: AsRef<[u8]>>(patterns: impl IntoIterator<Item = (P, V)>) -> Self {
+        let mut matcher = Self {
+            next: vec![ROOT; ALPHABET],
+            outputs: vec![None],
+            output_links: vec![None],
+        };
+        // the trie first, with `ROOT` as "no child" as the root is nobody's child.
+        for (pattern, value) in patterns {
+            let pattern = pattern.as_ref();
+            assert!(!pattern.is_empty(), "empty pattern");
+            let mut state = ROOT;
+            for &byte in pattern {
+                let index = state as usize * ALPHABET + byte as usize;
+                if matcher.next[index] == ROOT {
+                    matcher.next[index] = matcher.outputs.len() as u32;
+                    matcher.next.extend([ROOT; ALPHABET]);
+                    matcher.outputs.push(None);
+                    matcher.output_links.push(None);
+                }
+                state = matcher.next[index];
+            }
+            matcher.outputs[state as usize].get_or_insert((pattern.len(), value));
+        }
+
+        // then the failure links, breadth first so the link of a state is complete before its
+        // children need it. Missing transitions are replaced with the ones of the failure link.
+        let mut failure = vec![ROOT; matcher.outputs.len()];
+        let mut queue: VecDeque<u32> = matcher.next[..ALPHABET]
+            .iter()
+            .copied()
+            .filter(|&child| child != ROOT)
+            .collect();
+        while let Some(state) = queue.pop_front() {
+            let link = failure[state as usize];
+            for byte in 0..ALPHABET {
+                let index = state as usize * ALPHABET + byte;
+                let fallback = matcher.next[link as usize * ALPHABET + byte];
+                let child = matcher.next[index];
+                if child == ROOT {
+                    matcher.next[index] = fallback;
+                } else {
+                    failure[child as usize] = fallback;
+                    matcher.output_links[child as usize] =
+                        if matcher.outputs[fallback as usize].is_some() {
+                            Some(fallback)
+                        } else {
+                            matcher.output_links[fallback as usize]
+                        };
+                    queue.push_back(child);
+                }
+            }
+        }
+        matcher
+    }
+
+    /// Every occurrence of the patterns in `text`, by increasing end, then longest first.
+    pub fn find_iter<'m, 't>(&'m self, text: &'t [u8]) -> Matches<'m, 't, V> {
+        Matches {
+            matcher: self,
+            text,
+            position: 0,
+            state: ROOT,
+            pending: None,
+        }
+    }
+}
+
+/// Iterator over the occurrences of [`Matcher::find_iter`], allocation free.
+#[derive(Debug, Clone)]
+pub struct Matches<'m, 't, V> {
+    matcher: &'m Matcher<V>,
+    text: &'t [u8],
+    /// Bytes read so far.
+    position: usize,
+    state: u32,
+    /// Next state with an output to report for `position`.
+    pending: Option<u32>,
+}
+
+impl<'m, V> Iterator for Matches<'m, '_, V> {
+    type Item = Match<'m, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(state) = self.pending {
+                self.pending = self.matcher.output_links[state as usize];
+                if let Some((len, value)) = &self.matcher.outputs[state as usize] {
+                    return Some(Match {
+                        start: self.position - len,
+                        end: self.position,
+                        value,
+                    });
+                }
+                continue;
+            }
+            let &byte = self.text.get(self.position)?;
+            self.state = self.matcher.next[self.state as usize * ALPHABET + byte as usize];
+            self.position += 1;
+            self.pending = Some(self.state);
+        }
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::Matcher;
+    use crate::random::Rng;
+
+    fn matches(matcher: &Matcher<usize>, text: &str) -> Vec<(usize, usize, usize)> {
+        matcher
+            .find_iter(text.as_bytes())
+            .map(|m| (m.start, m.end, *m.value))
+            .collect()
+    }
+
+    #[test]
+    fn finds_overlapping_patterns() {
+        let matcher = Matcher::new([("eight", 8), ("two", 2), ("one", 1), ("ne", 0)]);
+        assert_eq!(
+            matches(&matcher, "eightwone"),
+            [(0, 5, 8), (4, 7, 2), (6, 9, 1), (7, 9, 0)]
+        );
+        assert_eq!(matches(&matcher, ""), []);
+        assert_eq!(matches(&matcher, "eighthree"), [(0, 5, 8)]);
+    }
+
+    #[test]
+    fn keeps_the_first_value_of_duplicates() {
+        let matcher = Matcher::new([("ab", 1), ("ab", 2), ("b", 3)]);
+        assert_eq!(
+            matches(&matcher, "abab"),
+            [(0, 2, 1), (1, 2, 3), (2, 4, 1), (3, 4, 3)]
+        );
+    }
+
+    #[test]
+    fn matches_like_brute_force() {
+        let mut rng = Rng::new(42);
+        let word = |rng: &mut Rng, max_len| {
+            let len = rng.range_usize(1, max_len);
+            (0..len)
+                .map(|_| b"abc"[rng.range_usize(0, 2)] as char)
+                .collect::<String>()
+        };
+        for _ in 0..300 {
+            let patterns: Vec<String> = (0..rng.range_usize(1, 6))
+                .map(|_| word(&mut rng, 4))
+                .collect();
+            let text = word(&mut rng, 30);
+            let matcher = Matcher::new(patterns.iter().enumerate().map(|(i, p)| (p, i)));
+
+            let mut expected = Vec::new();
+            for end in 1..=text.len() {
+                let mut ending: Vec<(usize, usize, usize)> = Vec::new();
+                for (i, pattern) in patterns.iter().enumerate() {
+                    let duplicate = patterns[..i].contains(pattern);
+                    if !duplicate && text[..end].ends_with(pattern.as_str()) {
+                        ending.push((end - pattern.len(), end, i));
+                    }
+                }
+                ending.sort();
+                expected.extend(ending);
+            }
+            assert_eq!(matches(&matcher, &text), expected, "{patterns:?} in {text}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "empty pattern")]
+    fn rejects_empty_patterns() {
+        Matcher::new([("", 0)]);
+    }
+}
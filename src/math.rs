@@ -0,0 +1,240 @@
+//! Exact integer arithmetic: extended GCD, Chinese remainder theorem, integer square roots
+//! and quadratic inequalities, all without going through floats.
+
+use num::BigUint;
+
+/// Returns `(g, x, y)` with `g = gcd(a, b) >= 0` and `a·x + b·y = g`.
+pub fn ext_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Solves the system of `x ≡ residue (mod modulus)` congruences, moduli need not be coprime.
+/// Returns `(x, m)` where `m` is the LCM of the moduli and `0 <= x < m` is the smallest solution,
+/// or [`None`] if the congruences are inconsistent.
+///
+/// # Panics
+/// If a modulus is not positive, or the LCM of the moduli overflows.
+pub fn crt(congruences: impl IntoIterator<Item = (i128, i128)>) -> Option<(i128, i128)> {
+    let (mut x, mut m) = (0, 1);
+    for (residue, modulus) in congruences {
+        assert!(modulus > 0, "modulus {modulus} is not positive");
+        let residue = residue.rem_euclid(modulus);
+        // x + m·t ≡ residue (mod modulus) <=> m·t ≡ residue - x (mod modulus)
+        let (g, p, _) = ext_gcd(m, modulus);
+        let diff = residue - x;
+        if diff % g != 0 {
+            return None;
+        }
+        let step = modulus / g;
+        let t = ((diff / g).rem_euclid(step) * p.rem_euclid(step)).rem_euclid(step);
+        let lcm = m
+            .checked_mul(step)
+            .expect("the LCM of the moduli overflows");
+        x = (x + m * t).rem_euclid(lcm);
+        m = lcm;
+    }
+    Some((x, m))
+}
+
+/// Largest `r` with `r² <= n`.
+pub fn isqrt_u128(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // start above the root from the bit length, then Newton's method only goes down.
+    let mut x = 1 << ((128 - n.leading_zeros()).div_ceil(2));
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Largest `r` with `r² <= n`.
+pub fn isqrt_u64(n: u64) -> u64 {
+    isqrt_u128(n.into()) as u64
+}
+
+/// Number of integers `x` with `x² - sum·x + product < 0`, that is strictly between the roots
+/// of the quadratic whose roots add up to `sum` and multiply to `product`.
+///
+/// In other words, the `x` for which `x·(sum - x) > product`.
+pub fn count_between_roots(sum: u64, product: u64) -> u64 {
+    let (b, c) = (u128::from(sum), u128::from(product));
+    let Some(discriminant) = (b * b).checked_sub(4 * c).filter(|&d| d > 0) else {
+        return 0;
+    };
+    // (b - isqrt(d)) / 2 is at most 1 below the smallest root, move up until strictly above it.
+    let mut low = (b - isqrt_u128(discriminant)) / 2;
+    while low <= b / 2 && low * (b - low) <= c {
+        low += 1;
+    }
+    let high = b - low;
+    if low > high {
+        return 0;
+    }
+    (high - low + 1) as u64
+}
+
+/// [`count_between_roots`] for numbers of any size, with big integers.
+pub fn count_between_roots_big(sum: &BigUint, product: &BigUint) -> BigUint {
+    let zero = BigUint::default();
+    let square = sum * sum;
+    let four_product = product << 2;
+    if square <= four_product {
+        return zero;
+    }
+    let discriminant = square - four_product;
+    let mut low = (sum - discriminant.sqrt()) >> 1;
+    let half = sum >> 1;
+    while low <= half && &low * (sum - &low) <= *product {
+        low += 1_u32;
+    }
+    let high = sum - &low;
+    if low > high {
+        return zero;
+    }
+    high - low + 1_u32
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use num::BigUint;
+
+    use super::{
+        count_between_roots, count_between_roots_big, crt, ext_gcd, isqrt_u128, isqrt_u64,
+    };
+    use crate::random::Rng;
+
+    #[test]
+    fn extended_gcd() {
+        for a in -30..=30 {
+            for b in -30..=30 {
+                let (g, x, y) = ext_gcd(a, b);
+                assert_eq!(a * x + b * y, g);
+                assert_eq!(g, num::integer::gcd(a, b), "{a} {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn chinese_remainders_like_brute_force() {
+        for m1 in 1..=12 {
+            for m2 in 1..=12 {
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let expected = (0..m1 * m2).find(|x| x % m1 == r1 && x % m2 == r2);
+                        let lcm = num::integer::lcm(m1, m2);
+                        assert_eq!(
+                            crt([(r1, m1), (r2, m2)]),
+                            expected.map(|x| (x, lcm)),
+                            "x = {r1} mod {m1}, x = {r2} mod {m2}"
+                        );
+                    }
+                }
+            }
+        }
+        assert_eq!(crt([]), Some((0, 1)));
+        assert_eq!(crt([(-1, 5)]), Some((4, 5)));
+        assert_eq!(crt([(2, 4), (3, 6)]), None);
+        assert_eq!(crt([(1, 4), (3, 6), (5, 10)]), Some((45, 60)));
+
+        let big = (1 << 61) - 1;
+        assert_eq!(
+            crt([(5, big), (7, big - 2)]).map(|(x, _)| (x % big, x % (big - 2))),
+            Some((5, 7))
+        );
+    }
+
+    #[test]
+    fn integer_square_roots() {
+        for n in 0..10_000u64 {
+            let r = isqrt_u64(n);
+            assert!(r * r <= n && (r + 1) * (r + 1) > n, "{n}");
+        }
+        let mut rng = Rng::new(38);
+        for _ in 0..10_000 {
+            let n = u128::from(rng.next_u64()) << rng.range(0, 64) | u128::from(rng.next_u64());
+            let r = isqrt_u128(n);
+            assert!(r * r <= n, "{n}");
+            assert!((r + 1).checked_mul(r + 1).is_none_or(|s| s > n), "{n}");
+        }
+        assert_eq!(isqrt_u64(u64::MAX), u64::from(u32::MAX));
+        assert_eq!(isqrt_u128(u128::MAX), u128::from(u64::MAX));
+        let square = u128::from(u64::MAX - 5).pow(2);
+        assert_eq!(isqrt_u128(square), u128::from(u64::MAX - 5));
+        assert_eq!(isqrt_u128(square - 1), u128::from(u64::MAX - 6));
+    }
+
+    #[test]
+    fn counts_between_roots_like_brute_force() {
+        for sum in 0..60u64 {
+            for product in 0..(sum * sum / 4 + 3) {
+                let expected = (0..=sum).filter(|x| x * (sum - x) > product).count() as u64;
+                assert_eq!(
+                    count_between_roots(sum, product),
+                    expected,
+                    "{sum} {product}"
+                );
+            }
+        }
+        // roots 3 and 2^40 - 3, both excluded.
+        let sum = 1 << 40;
+        assert_eq!(count_between_roots(sum, 3 * (sum - 3)), sum - 7);
+        assert_eq!(count_between_roots(sum, 3 * (sum - 3) - 1), sum - 5);
+        assert_eq!(count_between_roots(u64::MAX, 0), u64::MAX - 1);
+        assert_eq!(count_between_roots(u64::MAX, u64::MAX), u64::MAX - 3);
+    }
+
+    #[test]
+    fn counts_between_big_roots() {
+        let big = |n: u64| BigUint::from(n);
+        for sum in 0..40u64 {
+            for product in 0..(sum * sum / 4 + 3) {
+                assert_eq!(
+                    count_between_roots_big(&big(sum), &big(product)),
+                    big(count_between_roots(sum, product)),
+                    "{sum} {product}"
+                );
+            }
+        }
+        let mut rng = Rng::new(6);
+        for _ in 0..1000 {
+            let sum = rng.next_u64() >> rng.range(0, 63);
+            let max_product = (u128::from(sum) * u128::from(sum) / 4) as u64;
+            let product = rng.range(0, max_product.saturating_add(2).min(u64::MAX - 1));
+            assert_eq!(
+                count_between_roots_big(&big(sum), &big(product)),
+                big(count_between_roots(sum, product)),
+                "{sum} {product}"
+            );
+        }
+
+        // roots 10^30 and sum - 10^30, both excluded, far beyond u128 squares.
+        let sum: BigUint = "123456789012345678901234567890123456789".parse().unwrap();
+        let root = BigUint::from(10_u32).pow(30);
+        let product = &root * (&sum - &root);
+        let expected = &sum - (&root << 1) - 1_u32;
+        assert_eq!(count_between_roots_big(&sum, &product), expected);
+        assert_eq!(
+            count_between_roots_big(&sum, &(product - 1_u32)),
+            expected + 2_u32
+        );
+        assert_eq!(count_between_roots_big(&sum, &(&sum * &sum)), big(0));
+    }
+}
@@ -0,0 +1,158 @@
+/// Caches for recursive dynamic programming.
+///
+/// Both give the cache back to the closure computing a missing value, so it can recurse through it:
+///
+/// ```text
+/// fn fibonacci(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+///     memo.get_or_compute(n, |memo| {
+///         if n < 2 { n } else { fibonacci(n - 1, memo) + fibonacci(n - 2, memo) }
+///     })
+/// }
+/// ```
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A cache for any hashable key.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self {
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Returns the value cached for `key`, or computes it with `f` and caches it.
+    pub fn get_or_compute(&mut self, key: K, f: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            return value.clone();
+        }
+        let value = f(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A cache for keys made of `N` bounded integers, stored in a flat table: no hashing,
+/// but room for every possible key is allocated upfront.
+#[derive(Debug, Clone)]
+pub struct MemoTable<V, const N: usize> {
+    shape: [usize; N],
+    cells: Vec<Option<V>>,
+}
+
+impl<V: Clone, const N: usize> MemoTable<V, N> {
+    /// A table for the keys with `key[i] < shape[i]`.
+    pub fn new(shape: [usize; N]) -> Self {
+        Self {
+            shape,
+            cells: vec![None; shape.iter().product()],
+        }
+    }
+
+    fn index(&self, key: [usize; N]) -> usize {
+        key.iter().zip(self.shape).fold(0, |index, (&k, size)| {
+            assert!(k < size, "key {key:?} out of the {:?} table", self.shape);
+            index * size + k
+        })
+    }
+
+    /// Returns the value cached for `key`, or computes it with `f` and caches it.
+    ///
+    /// # Panics
+    /// If `key` is out of the shape of the table.
+    pub fn get_or_compute(&mut self, key: [usize; N], f: impl FnOnce(&mut Self) -> V) -> V {
+        let index = self.index(key);
+        if let Some(value) = &self.cells[index] {
+            return value.clone();
+        }
+        let value = f(self);
+        self.cells[index] = Some(value.clone());
+        value
+    }
+
+    pub fn get(&self, key: [usize; N]) -> Option<&V> {
+        self.cells[self.index(key)].as_ref()
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Memo, MemoTable};
+
+    fn fibonacci(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+        memo.get_or_compute(n, |memo| {
+            if n < 2 {
+                n
+            } else {
+                fibonacci(n - 1, memo) + fibonacci(n - 2, memo)
+            }
+        })
+    }
+
+    /// Lattice paths from `(0, 0)` to `(x, y)`.
+    fn paths(x: usize, y: usize, memo: &mut MemoTable<u64, 2>) -> u64 {
+        memo.get_or_compute([x, y], |memo| {
+            if x == 0 || y == 0 {
+                1
+            } else {
+                paths(x - 1, y, memo) + paths(x, y - 1, memo)
+            }
+        })
+    }
+
+    #[test]
+    fn memoizes_recursion() {
+        let mut memo = Memo::new();
+        assert_eq!(fibonacci(90, &mut memo), 2_880_067_194_370_816_120);
+        assert_eq!(memo.len(), 91);
+        assert_eq!(memo.get(&10), Some(&55));
+
+        let mut calls = 0;
+        let mut memo = Memo::new();
+        for _ in 0..3 {
+            memo.get_or_compute("key", |_| {
+                calls += 1;
+                calls
+            });
+        }
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn memoizes_in_tables() {
+        let mut table = MemoTable::new([17, 17]);
+        assert_eq!(paths(16, 16, &mut table), 601_080_390);
+        assert_eq!(table.get([2, 2]), Some(&6));
+        assert_eq!(table.get([16, 0]), Some(&1));
+        assert_eq!(table.get([0, 0]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of the")]
+    fn rejects_keys_out_of_the_table() {
+        let mut table = MemoTable::new([2, 3]);
+        table.get_or_compute([1, 3], |_| 0);
+    }
+}
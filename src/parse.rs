@@ -0,0 +1,397 @@
+/// Parsers for the shapes most inputs are made of: number lists, `key: values` lines,
+/// blank-line separated blocks and char grids.
+///
+/// Every parser works on slices of a [`Source`], so failures can point at the exact line and column:
+///
+/// ```text
+/// line 1, column 12: invalid number "4x": invalid digit found in string
+///  1 | Card 1: 41 4x | 83 86
+///    |            ^^
+/// ```
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::grid::{Grid, ParseGridError};
+
+/// The whole text being parsed. It is only used to locate the fragments passed to the parsers.
+#[derive(Clone, Copy, Debug)]
+pub struct Source<'a> {
+    text: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Numbered from 1.
+    pub line: usize,
+    /// Numbered from 1, in chars.
+    pub column: usize,
+    pub message: String,
+    /// The faulty line, and how many chars the carets cover.
+    snippet: String,
+    width: usize,
+}
+
+impl Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let margin = self.line.to_string().len();
+        writeln!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )?;
+        writeln!(f, " {} | {}", self.line, self.snippet)?;
+        write!(
+            f,
+            " {:margin$} | {:pad$}{}",
+            "",
+            "",
+            "^".repeat(self.width),
+            pad = self.column - 1
+        )
+    }
+}
+
+impl<'a> Source<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self { text }
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &'a str> {
+        self.text.lines()
+    }
+
+    /// Groups of consecutive non blank lines, each one as a single slice without its final newline.
+    pub fn blocks(&self) -> impl Iterator<Item = &'a str> {
+        let text = self.text;
+        let mut lines = text
+            .split_inclusive('\n')
+            .scan(0, |offset, line| {
+                let start = *offset;
+                *offset += line.len();
+                Some((start, line))
+            })
+            .peekable();
+        std::iter::from_fn(move || {
+            while lines.next_if(|(_, line)| line.trim().is_empty()).is_some() {}
+            let (start, first) = lines.next()?;
+            let mut end = start + first.trim_end_matches(['\n', '\r']).len();
+            while let Some((offset, line)) = lines.next_if(|(_, line)| !line.trim().is_empty()) {
+                end = offset + line.trim_end_matches(['\n', '\r']).len();
+            }
+            Some(&text[start..end])
+        })
+    }
+
+    /// Builds an error pointing at `fragment`, which must be a slice of the source.
+    ///
+    /// # Panics
+    /// If `fragment` is not a slice of the source.
+    pub fn error(&self, fragment: &str, message: impl Into<String>) -> ParseError {
+        let offset = self
+            .offset_of(fragment)
+            .expect("the fragment is not a slice of the source");
+        self.error_at(offset, fragment.chars().count(), message)
+    }
+
+    /// The line of `fragment`, numbered from 1, which must be a slice of the source.
+    ///
+    /// # Panics
+    /// If `fragment` is not a slice of the source.
+    pub fn line_of(&self, fragment: &str) -> usize {
+        let offset = self
+            .offset_of(fragment)
+            .expect("the fragment is not a slice of the source");
+        self.text[..offset].matches('\n').count() + 1
+    }
+
+    /// Builds an error pointing at `width` chars from the byte `offset` in the source.
+    pub fn error_at(&self, offset: usize, width: usize, message: impl Into<String>) -> ParseError {
+        let offset = offset.min(self.text.len());
+        let line_start = self.text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.text[offset..]
+            .find('\n')
+            .map_or(self.text.len(), |i| offset + i);
+        let snippet = self.text[line_start..line_end].trim_end_matches('\r');
+        let column = self.text[line_start..offset].chars().count() + 1;
+        ParseError {
+            line: self.text[..line_start].matches('\n').count() + 1,
+            column,
+            message: message.into(),
+            snippet: snippet.to_string(),
+            width: width.clamp(1, (snippet.chars().count() + 2).saturating_sub(column)),
+        }
+    }
+
+    /// Parses a whole fragment, surrounding whitespace excepted.
+    pub fn number<T>(&self, fragment: &'a str) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let token = fragment.trim();
+        if token.is_empty() {
+            return Err(self.error_at(self.end_of(fragment), 1, "expected a number"));
+        }
+        token
+            .parse()
+            .map_err(|e| self.error(token, format!("invalid number {token:?}: {e}")))
+    }
+
+    /// Parses numbers separated by whitespace and/or commas, like `41 48 83` or `1,1,3`.
+    pub fn numbers<T>(&self, fragment: &'a str) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        fragment
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|token| !token.is_empty())
+            .map(|token| self.number(token))
+            .collect()
+    }
+
+    /// Splits `fragment` around the first `delimiter`.
+    pub fn split_once(
+        &self,
+        fragment: &'a str,
+        delimiter: &str,
+    ) -> Result<(&'a str, &'a str), ParseError> {
+        fragment.split_once(delimiter).ok_or_else(|| {
+            self.error_at(self.end_of(fragment), 1, format!("expected {delimiter:?}"))
+        })
+    }
+
+    /// Splits a `key: value` line, both sides trimmed.
+    pub fn key_value(&self, line: &'a str) -> Result<(&'a str, &'a str), ParseError> {
+        let (key, value) = self.split_once(line, ":")?;
+        Ok((key.trim(), value.trim()))
+    }
+
+    /// Splits a `key: 1 2 3` line.
+    pub fn key_values<T>(&self, line: &'a str) -> Result<(&'a str, Vec<T>), ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let (key, values) = self.key_value(line)?;
+        Ok((key, self.numbers(values)?))
+    }
+
+    /// Like [`Source::key_value`], but the key must be `expected`.
+    pub fn expect_key(&self, line: &'a str, expected: &str) -> Result<&'a str, ParseError> {
+        let (key, value) = self.key_value(line)?;
+        if key != expected {
+            return Err(self.error(key, format!("expected {expected:?}")));
+        }
+        Ok(value)
+    }
+
+    /// Parses a char grid, `f` returns [`None`] for invalid chars.
+    pub fn grid<T>(
+        &self,
+        fragment: &'a str,
+        f: impl FnMut(char) -> Option<T>,
+    ) -> Result<Grid<T>, ParseError> {
+        let start = self
+            .offset_of(fragment)
+            .expect("the fragment is not a slice of the source");
+        let line_offset = |line: usize| {
+            fragment
+                .split_inclusive('\n')
+                .take(line - 1)
+                .map(str::len)
+                .sum::<usize>()
+        };
+        let char_offset = |line: usize, column: usize| {
+            let offset = line_offset(line);
+            offset
+                + fragment[offset..]
+                    .char_indices()
+                    .nth(column - 1)
+                    .map_or(0, |(i, _)| i)
+        };
+        Grid::try_parse(fragment, f).map_err(|e| match e {
+            ParseGridError::Empty => self.error_at(start, 1, "expected a grid"),
+            ParseGridError::Ragged {
+                line,
+                expected,
+                found,
+            } => {
+                let message =
+                    format!("expected {expected} cells like the first line, found {found}");
+                if found > expected {
+                    self.error_at(
+                        start + char_offset(line, expected + 1),
+                        found - expected,
+                        message,
+                    )
+                } else {
+                    let end = fragment[line_offset(line)..]
+                        .lines()
+                        .next()
+                        .map_or(0, str::len);
+                    self.error_at(start + line_offset(line) + end, 1, message)
+                }
+            }
+            ParseGridError::InvalidChar { line, column, char } => self.error_at(
+                start + char_offset(line, column),
+                1,
+                format!("unexpected {char:?}"),
+            ),
+        })
+    }
+
+    fn offset_of(&self, fragment: &str) -> Option<usize> {
+        let start = (fragment.as_ptr() as usize).checked_sub(self.text.as_ptr() as usize)?;
+        (start + fragment.len() <= self.text.len()).then_some(start)
+    }
+
+    fn end_of(&self, fragment: &str) -> usize {
+        self.offset_of(fragment)
+            .expect("the fragment is not a slice of the source")
+            + fragment.len()
+    }
+}
+
+/// Reports parse errors from a solution: prints them and gives up on the part.
+pub trait OrReport<T> {
+    fn or_report(self) -> Option<T>;
+}
+
+impl<T> OrReport<T> for Result<T, ParseError> {
+    fn or_report(self) -> Option<T> {
+        self.map_err(|e| eprintln!("Invalid input, {e}")).ok()
+    }
+}
+
+/// Every error is reported, for checks that find all the problems at once.
+impl<T> OrReport<T> for Result<T, Vec<ParseError>> {
+    fn or_report(self) -> Option<T> {
+        self.map_err(|errors| {
+            for e in errors {
+                eprintln!("Invalid input, {e}");
+            }
+        })
+        .ok()
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::Source;
+
+    #[test]
+    fn parses_numbers() {
+        let text = "41 48, 83\n-1,2";
+        let source = Source::new(text);
+        let mut lines = source.lines();
+        assert_eq!(
+            source.numbers::<u32>(lines.next().unwrap()),
+            Ok(vec![41, 48, 83])
+        );
+        assert_eq!(source.numbers::<i8>(lines.next().unwrap()), Ok(vec![-1, 2]));
+        assert_eq!(source.numbers::<u8>(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn locates_invalid_numbers() {
+        let text = "Card 1: 41 48 | 83\nCard 2: 41 4x | 83";
+        let source = Source::new(text);
+        let line = source.lines().nth(1).unwrap();
+        let (_, numbers) = source.key_value(line).unwrap();
+        let e = source.numbers::<u32>(numbers).unwrap_err();
+        assert_eq!((e.line, e.column), (2, 12));
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 12: invalid number \"4x\": invalid digit found in string\n \
+             2 | Card 2: 41 4x | 83\n   \
+               |            ^^"
+        );
+    }
+
+    #[test]
+    fn parses_key_values() {
+        let source = Source::new("Time:      7  15   30\nDistance  9");
+        let mut lines = source.lines();
+        let line = lines.next().unwrap();
+        assert_eq!(
+            source.key_values::<u64>(line),
+            Ok(("Time", vec![7, 15, 30]))
+        );
+        assert_eq!(source.expect_key(line, "Time"), Ok("7  15   30"));
+
+        let e = source.expect_key(line, "Distance").unwrap_err();
+        assert_eq!(
+            (e.line, e.column, e.message.as_str()),
+            (1, 1, "expected \"Distance\"")
+        );
+
+        let e = source.key_value(lines.next().unwrap()).unwrap_err();
+        assert_eq!(
+            (e.line, e.column, e.message.as_str()),
+            (2, 12, "expected \":\"")
+        );
+        assert!(e.to_string().ends_with("| Distance  9\n   |            ^"));
+    }
+
+    #[test]
+    fn splits_blocks() {
+        let source = Source::new("\na\nb\n\n\n  \nc\r\n\r\nd\ne\n\n");
+        let blocks: Vec<&str> = source.blocks().collect();
+        assert_eq!(blocks, ["a\nb", "c", "d\ne"]);
+
+        let e = source.number::<u8>(blocks[2]).unwrap_err();
+        assert_eq!((e.line, e.column), (9, 1));
+        assert_eq!(source.line_of(blocks[2]), 9);
+        assert_eq!(Source::new("").blocks().count(), 0);
+    }
+
+    #[test]
+    fn parses_grids() {
+        let text = "seeds\n\n#.#\n.#.\n";
+        let source = Source::new(text);
+        let block = source.blocks().nth(1).unwrap();
+        let parse = |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        };
+        let grid = source.grid(block, parse).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 1)], true);
+
+        let source = Source::new("x\n\n#.#\n.?.\n");
+        let block = source.blocks().nth(1).unwrap();
+        let e = source.grid(block, parse).unwrap_err();
+        assert_eq!(
+            (e.line, e.column, e.message.as_str()),
+            (4, 2, "unexpected '?'")
+        );
+
+        let source = Source::new("#.#\n.#.#.\n#");
+        let e = source.grid(source.text(), parse).unwrap_err();
+        assert_eq!((e.line, e.column), (2, 4));
+        assert!(e.to_string().ends_with("|    ^^"));
+
+        let source = Source::new("#.#\n.#\n");
+        let e = source.grid(source.text(), parse).unwrap_err();
+        assert_eq!((e.line, e.column), (2, 3));
+    }
+
+    #[test]
+    fn errors_past_the_end() {
+        let source = Source::new("7\n");
+        let e = source.number::<u8>(&source.text()[2..]).unwrap_err();
+        assert_eq!(
+            (e.line, e.column, e.message.as_str()),
+            (2, 1, "expected a number")
+        );
+    }
+}
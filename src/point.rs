@@ -0,0 +1,341 @@
+/// Directions and points on a 2D lattice where `y` grows downwards, as in the puzzle inputs.
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::ops::{Add, Sub};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+impl Direction {
+    /// All directions, clockwise from [`Direction::Up`].
+    pub const ALL: [Direction; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    pub fn iter() -> impl Iterator<Item = Direction> {
+        Self::ALL.into_iter()
+    }
+
+    /// Quarter turn clockwise.
+    #[must_use]
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    /// Quarter turn counterclockwise.
+    #[must_use]
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+
+    pub fn axis(self) -> Axis {
+        match self {
+            Self::Up | Self::Down => Axis::Vertical,
+            Self::Right | Self::Left => Axis::Horizontal,
+        }
+    }
+
+    /// `(dx, dy)` of a single step.
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Self::Up => (0, -1),
+            Self::Right => (1, 0),
+            Self::Down => (0, 1),
+            Self::Left => (-1, 0),
+        }
+    }
+
+    /// The `U/R/D/L` letter, as in dig plans.
+    pub fn letter(self) -> char {
+        ['U', 'R', 'D', 'L'][self as usize]
+    }
+
+    pub fn arrow(self) -> char {
+        ['↑', '→', '↓', '←'][self as usize]
+    }
+}
+
+impl Axis {
+    #[must_use]
+    pub fn perpendicular(self) -> Self {
+        match self {
+            Self::Horizontal => Self::Vertical,
+            Self::Vertical => Self::Horizontal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseDirectionError(pub char);
+
+impl Error for ParseDirectionError {}
+
+impl Display for ParseDirectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not a direction", self.0)
+    }
+}
+
+/// Accepts the `U/R/D/L` letters and the `^>v<` and `↑→↓←` arrows.
+impl TryFrom<char> for Direction {
+    type Error = ParseDirectionError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            'U' | '^' | '↑' => Ok(Self::Up),
+            'R' | '>' | '→' => Ok(Self::Right),
+            'D' | 'v' | '↓' => Ok(Self::Down),
+            'L' | '<' | '←' => Ok(Self::Left),
+            c => Err(ParseDirectionError(c)),
+        }
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.arrow())
+    }
+}
+
+/// A point with unsigned coordinates, usually a position in a grid.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Point {
+    pub const fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    /// Moves a single step towards `dir`, returns [`None`] on underflow or overflow.
+    pub fn checked_step(self, dir: Direction) -> Option<Self> {
+        self.checked_step_n(dir, 1)
+    }
+
+    /// Moves `n` steps towards `dir`, returns [`None`] on underflow or overflow.
+    pub fn checked_step_n(self, dir: Direction, n: usize) -> Option<Self> {
+        Some(match dir {
+            Direction::Up => Self::new(self.x, self.y.checked_sub(n)?),
+            Direction::Right => Self::new(self.x.checked_add(n)?, self.y),
+            Direction::Down => Self::new(self.x, self.y.checked_add(n)?),
+            Direction::Left => Self::new(self.x.checked_sub(n)?, self.y),
+        })
+    }
+
+    /// Moves a single step towards `dir`.
+    ///
+    /// # Panics
+    /// On underflow or overflow, like stepping up from `y = 0`, see [`Point::checked_step`].
+    #[must_use]
+    pub fn step(self, dir: Direction) -> Self {
+        self.step_n(dir, 1)
+    }
+
+    /// Moves `n` steps towards `dir`.
+    ///
+    /// # Panics
+    /// On underflow or overflow, see [`Point::checked_step_n`].
+    #[must_use]
+    pub fn step_n(self, dir: Direction, n: usize) -> Self {
+        self.checked_step_n(dir, n)
+            .unwrap_or_else(|| panic!("moving {self} by {n} {dir} leaves the usize range"))
+    }
+
+    pub fn manhattan(self, other: Self) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+impl From<(usize, usize)> for Point {
+    fn from((x, y): (usize, usize)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<Point> for (usize, usize) {
+    fn from(point: Point) -> Self {
+        (point.x, point.y)
+    }
+}
+
+impl TryFrom<IPoint> for Point {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(point: IPoint) -> Result<Self, Self::Error> {
+        Ok(Self::new(point.x.try_into()?, point.y.try_into()?))
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// A point with signed coordinates, for walks that can go left of or above their start.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct IPoint {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl IPoint {
+    pub const fn new(x: isize, y: isize) -> Self {
+        Self { x, y }
+    }
+
+    /// Moves a single step towards `dir`, returns [`None`] on overflow.
+    pub fn checked_step(self, dir: Direction) -> Option<Self> {
+        self.checked_step_n(dir, 1)
+    }
+
+    /// Moves `n` steps towards `dir`, returns [`None`] on overflow.
+    pub fn checked_step_n(self, dir: Direction, n: usize) -> Option<Self> {
+        let n = isize::try_from(n).ok()?;
+        let (dx, dy) = dir.delta();
+        Some(Self::new(
+            self.x.checked_add(dx.checked_mul(n)?)?,
+            self.y.checked_add(dy.checked_mul(n)?)?,
+        ))
+    }
+
+    /// Moves a single step towards `dir`.
+    ///
+    /// # Panics
+    /// On overflow, see [`IPoint::checked_step`].
+    #[must_use]
+    pub fn step(self, dir: Direction) -> Self {
+        self.step_n(dir, 1)
+    }
+
+    /// Moves `n` steps towards `dir`.
+    ///
+    /// # Panics
+    /// On overflow, see [`IPoint::checked_step_n`].
+    #[must_use]
+    pub fn step_n(self, dir: Direction, n: usize) -> Self {
+        self.checked_step_n(dir, n)
+            .unwrap_or_else(|| panic!("moving {self} by {n} {dir} overflows"))
+    }
+
+    pub fn manhattan(self, other: Self) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+impl From<(isize, isize)> for IPoint {
+    fn from((x, y): (isize, isize)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl TryFrom<Point> for IPoint {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(point: Point) -> Result<Self, Self::Error> {
+        Ok(Self::new(point.x.try_into()?, point.y.try_into()?))
+    }
+}
+
+impl Add for IPoint {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for IPoint {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Display for IPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Axis, Direction, IPoint, Point};
+
+    #[test]
+    fn turns() {
+        for dir in Direction::iter() {
+            assert_eq!(dir.turn_right().turn_left(), dir);
+            assert_eq!(dir.turn_right().turn_right(), dir.opposite());
+            assert_eq!(dir.opposite().opposite(), dir);
+            assert_eq!(dir.turn_left().axis(), dir.axis().perpendicular());
+        }
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Left.axis(), Axis::Horizontal);
+    }
+
+    #[test]
+    fn converts_chars() {
+        for dir in Direction::iter() {
+            assert_eq!(Direction::try_from(dir.letter()), Ok(dir));
+            assert_eq!(Direction::try_from(dir.arrow()), Ok(dir));
+        }
+        assert_eq!(Direction::try_from('v'), Ok(Direction::Down));
+        assert_eq!(Direction::try_from('<'), Ok(Direction::Left));
+        assert!(Direction::try_from('x').is_err());
+        assert_eq!(Direction::Right.to_string(), "→");
+    }
+
+    #[test]
+    fn steps_unsigned() {
+        let p = Point::new(2, 1);
+        assert_eq!(p.checked_step(Direction::Up), Some(Point::new(2, 0)));
+        assert_eq!(p.checked_step_n(Direction::Up, 2), None);
+        assert_eq!(p.step_n(Direction::Left, 2), Point::new(0, 1));
+        assert_eq!(p.step(Direction::Down), Point::new(2, 2));
+        assert_eq!(
+            Point::new(usize::MAX, 0).checked_step(Direction::Right),
+            None
+        );
+        assert_eq!(p.manhattan(Point::new(0, 4)), 5);
+    }
+
+    #[test]
+    fn steps_signed() {
+        let p = IPoint::new(0, 0);
+        assert_eq!(p.step_n(Direction::Up, 3), IPoint::new(0, -3));
+        assert_eq!(p.step(Direction::Left), IPoint::new(-1, 0));
+        assert_eq!(
+            IPoint::new(isize::MAX, 0).checked_step(Direction::Right),
+            None
+        );
+        assert_eq!(p.checked_step_n(Direction::Down, usize::MAX), None);
+        assert_eq!(p.manhattan(IPoint::new(-3, 4)), 7);
+        assert_eq!(Point::try_from(IPoint::new(-1, 0)).ok(), None);
+        assert_eq!(IPoint::new(1, 2) - IPoint::new(1, 1) + p, IPoint::new(0, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "moving (0, 0) by 1 ↑ leaves the usize range")]
+    fn panics_stepping_out_of_range() {
+        let _ = Point::new(0, 0).step(Direction::Up);
+    }
+}
@@ -0,0 +1,235 @@
+/// Minimal property-based testing harness.
+///
+/// Test cases are generated from an [`Arbitrary`] implementation with a growing size.
+/// When a case fails (either the property returns an error or the code under test panics),
+/// it is greedily shrunk to a minimal reproduction which is reported in the panic message.
+use std::env;
+use std::fmt::Display;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::random::Rng;
+
+/// A type that can be randomly generated and shrunk.
+/// The [`Display`] implementation is used to report failing cases, so it usually renders the puzzle input.
+pub trait Arbitrary: Clone + Display {
+    /// Generates a random value. `size` grows with the number of cases and should bound the value.
+    fn arbitrary(rng: &mut Rng, size: usize) -> Self;
+
+    /// Returns "smaller" candidates of this value, simplest first.
+    fn shrink(&self) -> Vec<Self> {
+        Vec::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    pub cases: usize,
+    pub max_size: usize,
+    pub seed: u64,
+}
+
+impl Default for Config {
+    /// The defaults can be overridden with the `AOC_PROPERTY_CASES` and `AOC_PROPERTY_SEED` environment variables.
+    fn default() -> Self {
+        let var = |name: &str| env::var(name).ok().and_then(|v| v.parse().ok());
+        Self {
+            cases: var("AOC_PROPERTY_CASES").map_or(200, |c: u64| c as usize),
+            max_size: 10,
+            seed: var("AOC_PROPERTY_SEED").unwrap_or(0x00c0_ffee),
+        }
+    }
+}
+
+/// Checks `property` against random cases with the default [`Config`].
+/// Panics with a minimal failing case if the property does not hold.
+pub fn check<T: Arbitrary>(property: impl Fn(&T) -> Result<(), String>) {
+    check_with(Config::default(), property);
+}
+
+/// Checks `property` against random cases.
+/// Panics with a minimal failing case if the property does not hold.
+pub fn check_with<T: Arbitrary>(config: Config, property: impl Fn(&T) -> Result<(), String>) {
+    let mut rng = Rng::new(config.seed);
+
+    for case in 0..config.cases {
+        let size = 1 + case * config.max_size / config.cases.max(1);
+        let value = T::arbitrary(&mut rng, size);
+
+        if let Err(error) = run(&property, &value) {
+            let (minimal, error, steps) = shrink(&property, value.clone(), error);
+            panic!(
+                "property failed at case {case} (seed {:#x}), shrunk {steps} times.\n\
+                 error: {error}\n\
+                 minimal input:\n{minimal}\n\
+                 original input:\n{value}",
+                config.seed
+            );
+        }
+    }
+}
+
+fn run<T>(property: &impl Fn(&T) -> Result<(), String>, value: &T) -> Result<(), String> {
+    match catch_unwind(AssertUnwindSafe(|| property(value))) {
+        Ok(result) => result,
+        Err(panic) => Err(panic
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| panic.downcast_ref::<&str>().map(|s| (*s).to_string()))
+            .map_or_else(|| "panicked".into(), |msg| format!("panicked: {msg}"))),
+    }
+}
+
+fn shrink<T: Arbitrary>(
+    property: &impl Fn(&T) -> Result<(), String>,
+    mut value: T,
+    mut error: String,
+) -> (T, String, usize) {
+    let mut steps = 0;
+
+    'shrinking: loop {
+        for candidate in value.shrink() {
+            if let Err(candidate_error) = run(property, &candidate) {
+                value = candidate;
+                error = candidate_error;
+                steps += 1;
+                continue 'shrinking;
+            }
+        }
+        return (value, error, steps);
+    }
+}
+
+/// Shrinks a vector by removing chunks of elements, then by shrinking single elements.
+/// Vectors are never shrunk below `min_len` elements.
+pub fn shrink_vec<T: Clone>(
+    values: &[T],
+    min_len: usize,
+    shrink_element: impl Fn(&T) -> Vec<T>,
+) -> Vec<Vec<T>> {
+    let mut candidates = Vec::new();
+
+    let mut chunk = values.len() / 2;
+    while chunk > 0 {
+        if values.len() - chunk >= min_len {
+            for start in (0..=values.len() - chunk).step_by(chunk) {
+                let mut candidate = values[..start].to_vec();
+                candidate.extend_from_slice(&values[start + chunk..]);
+                candidates.push(candidate);
+            }
+        }
+        chunk /= 2;
+    }
+
+    for (i, value) in values.iter().enumerate() {
+        for smaller in shrink_element(value) {
+            let mut candidate = values.to_vec();
+            candidate[i] = smaller;
+            candidates.push(candidate);
+        }
+    }
+
+    candidates
+}
+
+/// Shrinks a number towards `min`.
+#[must_use]
+pub fn shrink_u64(value: u64, min: u64) -> Vec<u64> {
+    let mut candidates = Vec::new();
+    if value <= min {
+        return candidates;
+    }
+    candidates.push(min);
+    let mut delta = (value - min) / 2;
+    while delta > 0 {
+        if value - delta != min {
+            candidates.push(value - delta);
+        }
+        delta /= 2;
+    }
+    if value - 1 != min && !candidates.contains(&(value - 1)) {
+        candidates.push(value - 1);
+    }
+    candidates
+}
+
+/// Shrinks a number towards `min`.
+#[must_use]
+pub fn shrink_usize(value: usize, min: usize) -> Vec<usize> {
+    #[allow(clippy::cast_possible_truncation)]
+    shrink_u64(value as u64, min as u64)
+        .into_iter()
+        .map(|v| v as usize)
+        .collect()
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use std::fmt::Display;
+
+    use super::{check, check_with, shrink_u64, shrink_vec, Arbitrary, Config};
+    use crate::random::Rng;
+
+    #[derive(Clone, Debug)]
+    struct Numbers(Vec<u64>);
+
+    impl Display for Numbers {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+
+    impl Arbitrary for Numbers {
+        fn arbitrary(rng: &mut Rng, size: usize) -> Self {
+            let len = rng.range_usize(0, size);
+            Numbers((0..len).map(|_| rng.range(0, 100)).collect())
+        }
+
+        fn shrink(&self) -> Vec<Self> {
+            shrink_vec(&self.0, 0, |&n| shrink_u64(n, 0))
+                .into_iter()
+                .map(Numbers)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn passes_valid_property() {
+        check(|n: &Numbers| {
+            let mut sorted = n.0.clone();
+            sorted.sort_unstable();
+            if sorted.len() == n.0.len() {
+                Ok(())
+            } else {
+                Err("lost elements".into())
+            }
+        });
+    }
+
+    #[test]
+    fn shrinks_to_minimal_case() {
+        let result = std::panic::catch_unwind(|| {
+            check_with(
+                Config {
+                    cases: 100,
+                    max_size: 20,
+                    seed: 7,
+                },
+                |n: &Numbers| {
+                    if n.0.iter().any(|&v| v >= 50) {
+                        Err("too big".into())
+                    } else {
+                        Ok(())
+                    }
+                },
+            );
+        });
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("minimal input:\n[50]\n"), "{message}");
+    }
+
+    #[test]
+    fn shrinks_numbers() {
+        assert_eq!(shrink_u64(10, 0), vec![0, 5, 8, 9]);
+        assert!(shrink_u64(3, 3).is_empty());
+    }
+}
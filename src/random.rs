@@ -0,0 +1,83 @@
+/// Small deterministic pseudo random number generator (`SplitMix64`).
+/// Good enough to generate test inputs, without pulling in a dependency.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in the inclusive range `low..=high`.
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        assert!(low <= high, "empty range {low}..={high}");
+        match (high - low).checked_add(1) {
+            Some(span) => low + self.next_u64() % span,
+            None => self.next_u64(),
+        }
+    }
+
+    /// Returns a value in the inclusive range `low..=high`.
+    pub fn range_usize(&mut self, low: usize, high: usize) -> usize {
+        #[allow(clippy::cast_possible_truncation)]
+        let value = self.range(low as u64, high as u64) as usize;
+        value
+    }
+
+    /// Returns `true` with a probability of `numerator / denominator`.
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.next_u64() % denominator < numerator
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.chance(1, 2)
+    }
+
+    /// Picks a random element of a non-empty slice.
+    pub fn choose<'a, T>(&mut self, values: &'a [T]) -> &'a T {
+        &values[self.range_usize(0, values.len() - 1)]
+    }
+
+    /// Shuffles a slice in place (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            values.swap(i, self.range_usize(0, i));
+        }
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::Rng;
+
+    #[test]
+    fn is_deterministic() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn stays_in_range() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            let v = rng.range(3, 7);
+            assert!((3..=7).contains(&v));
+        }
+        assert_eq!(rng.range(5, 5), 5);
+        rng.range(0, u64::MAX);
+    }
+}
@@ -0,0 +1,568 @@
+/// Sets of integers stored as intervals, and piecewise maps shifting whole intervals at once.
+///
+/// Intervals are half-open `start..end` ranges of `u64`, so `u64::MAX` itself can never be part of one.
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// A set of `u64` as sorted, disjoint and non adjacent intervals.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    ranges: Vec<Range<u64>>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a set from any ranges, overlapping, adjacent or empty ones included, in O(n log n).
+    pub fn from_ranges(ranges: impl IntoIterator<Item = Range<u64>>) -> Self {
+        let mut ranges: Vec<Range<u64>> = ranges.into_iter().filter(|r| !r.is_empty()).collect();
+        ranges.sort_unstable_by_key(|r| r.start);
+        let mut normalized: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match normalized.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => normalized.push(range),
+            }
+        }
+        Self { ranges: normalized }
+    }
+
+    pub fn ranges(&self) -> &[Range<u64>] {
+        &self.ranges
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Range<u64>> + '_ {
+        self.ranges.iter().cloned()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of values in the set.
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().map(|r| r.end - r.start).sum()
+    }
+
+    pub fn min(&self) -> Option<u64> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        self.ranges.last().map(|r| r.end - 1)
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        self.ranges
+            .binary_search_by(|r| {
+                if r.end <= value {
+                    Ordering::Less
+                } else if r.start > value {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    pub fn insert(&mut self, range: Range<u64>) {
+        *self = self.union(&Self::from_ranges([range]));
+    }
+
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_ranges(self.iter().chain(other.iter()))
+    }
+
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let (start, end) = (a.start.max(b.start), a.end.min(b.end));
+            if start < end {
+                ranges.push(start..end);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { ranges }
+    }
+
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+        for range in &self.ranges {
+            let mut start = range.start;
+            // skip the removed ranges entirely before this one, they can't touch the next ones either.
+            while other.ranges.get(j).is_some_and(|b| b.end <= start) {
+                j += 1;
+            }
+            let mut k = j;
+            while let Some(b) = other.ranges.get(k).filter(|b| b.start < range.end) {
+                if start < b.start {
+                    ranges.push(start..b.start);
+                }
+                start = start.max(b.end);
+                k += 1;
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+        Self { ranges }
+    }
+}
+
+impl FromIterator<Range<u64>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Range<u64>>>(iter: I) -> Self {
+        Self::from_ranges(iter)
+    }
+}
+
+/// Values in `source` are sent to `destination + (value - source.start)`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Piece {
+    pub source: Range<u64>,
+    pub destination: u64,
+}
+
+impl Piece {
+    fn apply(&self, value: u64) -> u64 {
+        self.destination + (value - self.source.start)
+    }
+
+    fn destination_end(&self) -> u64 {
+        self.destination + (self.source.end - self.source.start)
+    }
+}
+
+/// A map of `u64` made of pieces shifting intervals, the identity outside of them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PiecewiseMap {
+    /// Sorted, disjoint, and never the identity.
+    pieces: Vec<Piece>,
+}
+
+impl PiecewiseMap {
+    /// The identity.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a map from `pieces`, where the first ones win when their sources overlap.
+    ///
+    /// # Panics
+    /// If a piece is sent past `u64::MAX`.
+    pub fn from_pieces(pieces: impl IntoIterator<Item = Piece>) -> Self {
+        let pieces: Vec<Piece> = pieces
+            .into_iter()
+            .filter(|p| !p.source.is_empty())
+            .collect();
+        for piece in &pieces {
+            assert!(
+                piece
+                    .destination
+                    .checked_add(piece.source.end - piece.source.start)
+                    .is_some(),
+                "{piece:?} is sent past u64::MAX"
+            );
+        }
+        let mut sorted = pieces.clone();
+        sorted.sort_unstable_by_key(|p| p.source.start);
+        if sorted
+            .windows(2)
+            .all(|pair| pair[0].source.end <= pair[1].source.start)
+        {
+            return Self::normalized(sorted);
+        }
+
+        // overlapping sources: clip every piece by the ones before it.
+        let mut covered = IntervalSet::new();
+        let mut kept = Vec::new();
+        for piece in pieces {
+            let source = IntervalSet::from_ranges([piece.source.clone()]);
+            for range in source.difference(&covered).iter() {
+                kept.push(Piece {
+                    destination: piece.apply(range.start),
+                    source: range,
+                });
+            }
+            covered = covered.union(&source);
+        }
+        Self::normalized(kept)
+    }
+
+    /// Sorts disjoint pieces, drops the identity ones and merges the contiguous ones.
+    fn normalized(mut pieces: Vec<Piece>) -> Self {
+        pieces.sort_unstable_by_key(|p| p.source.start);
+        let mut normalized: Vec<Piece> = Vec::with_capacity(pieces.len());
+        for piece in pieces {
+            if piece.source.start == piece.destination {
+                continue;
+            }
+            match normalized.last_mut() {
+                Some(last)
+                    if last.source.end == piece.source.start
+                        && last.destination_end() == piece.destination =>
+                {
+                    last.source.end = piece.source.end;
+                }
+                _ => normalized.push(piece),
+            }
+        }
+        Self { pieces: normalized }
+    }
+
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.pieces.is_empty()
+    }
+
+    pub fn apply(&self, value: u64) -> u64 {
+        let i = self.pieces.partition_point(|p| p.source.end <= value);
+        match self.pieces.get(i) {
+            Some(piece) if piece.source.start <= value => piece.apply(value),
+            _ => value,
+        }
+    }
+
+    /// Image of a whole set, in O((n + m) log(n + m)) for a set of n intervals over m pieces.
+    pub fn apply_set(&self, set: &IntervalSet) -> IntervalSet {
+        let mut image = Vec::new();
+        for range in set.iter() {
+            let first = self.pieces.partition_point(|p| p.source.end <= range.start);
+            let mut start = range.start;
+            for piece in self.pieces[first..]
+                .iter()
+                .take_while(|p| p.source.start < range.end)
+            {
+                if start < piece.source.start {
+                    image.push(start..piece.source.start);
+                }
+                let end = piece.source.end.min(range.end);
+                let from = start.max(piece.source.start);
+                image.push(piece.apply(from)..piece.apply(end - 1) + 1);
+                start = end;
+            }
+            if start < range.end {
+                image.push(start..range.end);
+            }
+        }
+        IntervalSet::from_ranges(image)
+    }
+
+    /// The values sent into `set`, the inverse image. Several pieces can send values to the same
+    /// place, so the inverse of a single value can be many intervals.
+    pub fn preimage(&self, set: &IntervalSet) -> IntervalSet {
+        let mut preimage = Vec::new();
+        for segment in self.segments() {
+            let (image_start, image_end) = (segment.destination, segment.destination_end());
+            let first = set.ranges.partition_point(|r| r.end <= image_start);
+            for range in set.ranges[first..]
+                .iter()
+                .take_while(|r| r.start < image_end)
+            {
+                let from = image_start.max(range.start);
+                let to = image_end.min(range.end);
+                let source_start = segment.source.start + (from - image_start);
+                preimage.push(source_start..source_start + (to - from));
+            }
+        }
+        IntervalSet::from_ranges(preimage)
+    }
+
+    /// The smallest image of a value of `set`. Each piece shifts its values as a whole, so the
+    /// minimum is the image of a breakpoint: the start of a range of `set`, or a piece boundary
+    /// inside it.
+    pub fn min_over(&self, set: &IntervalSet) -> Option<u64> {
+        let mut min = None;
+        for range in set.iter() {
+            let first = self.pieces.partition_point(|p| p.source.end <= range.start);
+            let boundaries = self.pieces[first..]
+                .iter()
+                .take_while(|p| p.source.start < range.end)
+                .flat_map(|p| [p.source.start, p.source.end])
+                .filter(|&value| range.contains(&value));
+            for value in std::iter::once(range.start).chain(boundaries) {
+                let image = self.apply(value);
+                min = Some(min.map_or(image, |min: u64| min.min(image)));
+            }
+        }
+        min
+    }
+
+    /// The pieces along with the identity gaps between them, covering `0..u64::MAX`.
+    fn segments(&self) -> Vec<Piece> {
+        let mut segments = Vec::with_capacity(2 * self.pieces.len() + 1);
+        let mut start = 0;
+        for piece in &self.pieces {
+            if start < piece.source.start {
+                segments.push(Piece {
+                    source: start..piece.source.start,
+                    destination: start,
+                });
+            }
+            segments.push(piece.clone());
+            start = piece.source.end;
+        }
+        if start < u64::MAX {
+            segments.push(Piece {
+                source: start..u64::MAX,
+                destination: start,
+            });
+        }
+        segments
+    }
+
+    /// The map applying `self`, then `then`.
+    #[must_use]
+    pub fn then(&self, then: &PiecewiseMap) -> PiecewiseMap {
+        let next = then.segments();
+        let mut pieces = Vec::new();
+        for segment in self.segments() {
+            let (image_start, image_end) = (segment.destination, segment.destination_end());
+            let first = next.partition_point(|p| p.source.end <= image_start);
+            for piece in next[first..]
+                .iter()
+                .take_while(|p| p.source.start < image_end)
+            {
+                let from = image_start.max(piece.source.start);
+                let to = image_end.min(piece.source.end);
+                let source_start = segment.source.start + (from - image_start);
+                pieces.push(Piece {
+                    source: source_start..source_start + (to - from),
+                    destination: piece.apply(from),
+                });
+            }
+        }
+        Self::normalized(pieces)
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{IntervalSet, Piece, PiecewiseMap};
+
+    const UNIVERSE: u64 = 7;
+
+    /// Every set included in `0..UNIVERSE`, as bitmasks.
+    fn masks() -> impl Iterator<Item = u32> {
+        0..1 << UNIVERSE
+    }
+
+    fn from_mask(mask: u32) -> IntervalSet {
+        IntervalSet::from_ranges(
+            (0..UNIVERSE)
+                .filter(|&v| mask & (1 << v) != 0)
+                .map(|v| v..v + 1),
+        )
+    }
+
+    fn to_mask(set: &IntervalSet) -> u32 {
+        (0..UNIVERSE)
+            .filter(|&v| set.contains(v))
+            .fold(0, |mask, v| mask | 1 << v)
+    }
+
+    fn assert_normalized(set: &IntervalSet) {
+        for range in set.ranges() {
+            assert!(range.start < range.end, "{set:?}");
+        }
+        for pair in set.ranges().windows(2) {
+            assert!(pair[0].end < pair[1].start, "{set:?}");
+        }
+    }
+
+    #[test]
+    fn normalizes() {
+        let set = IntervalSet::from_ranges([5..8, 0..2, 2..3, 7..9, 4..4, 12..13, 6..7]);
+        assert_eq!(set.ranges(), [0..3, 5..9, 12..13]);
+        assert_eq!(set.len(), 8);
+        assert_eq!((set.min(), set.max()), (Some(0), Some(12)));
+        assert!(set.contains(8) && !set.contains(9) && !set.contains(4));
+        assert!(IntervalSet::from_ranges([3..3]).is_empty());
+
+        let mut set = IntervalSet::new();
+        set.insert(4..6);
+        set.insert(0..1);
+        set.insert(1..4);
+        assert_eq!(set.ranges(), [0..6]);
+    }
+
+    #[test]
+    fn set_operations_match_bitmasks() {
+        for a in masks() {
+            let set_a = from_mask(a);
+            assert_normalized(&set_a);
+            assert_eq!(to_mask(&set_a), a);
+            assert_eq!(set_a.len(), u64::from(a.count_ones()));
+            for b in masks() {
+                let set_b = from_mask(b);
+                for (result, expected) in [
+                    (set_a.union(&set_b), a | b),
+                    (set_a.intersection(&set_b), a & b),
+                    (set_a.difference(&set_b), a & !b),
+                ] {
+                    assert_normalized(&result);
+                    assert_eq!(to_mask(&result), expected, "{set_a:?} {set_b:?}");
+                }
+            }
+        }
+    }
+
+    /// Every piece with its source and destination in `0..=UNIVERSE`.
+    fn pieces() -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        for start in 0..UNIVERSE {
+            for end in start + 1..=UNIVERSE {
+                for destination in 0..=UNIVERSE {
+                    pieces.push(Piece {
+                        source: start..end,
+                        destination,
+                    });
+                }
+            }
+        }
+        pieces
+    }
+
+    /// The first piece containing `value` wins.
+    fn brute_apply(pieces: &[Piece], value: u64) -> u64 {
+        pieces
+            .iter()
+            .find(|p| p.source.contains(&value))
+            .map_or(value, |p| p.destination + value - p.source.start)
+    }
+
+    #[test]
+    fn applies_first_matching_piece() {
+        let pieces = pieces();
+        for a in pieces.iter().step_by(3) {
+            for b in &pieces {
+                let rules = [a.clone(), b.clone()];
+                let map = PiecewiseMap::from_pieces(rules.clone());
+                for window in map.pieces().windows(2) {
+                    assert!(window[0].source.end <= window[1].source.start, "{map:?}");
+                }
+                for value in 0..2 * UNIVERSE {
+                    assert_eq!(map.apply(value), brute_apply(&rules, value), "{rules:?}");
+                }
+            }
+        }
+        assert!(PiecewiseMap::from_pieces([Piece {
+            source: 3..5,
+            destination: 3
+        }])
+        .is_identity());
+    }
+
+    #[test]
+    fn maps_whole_sets() {
+        let pieces = pieces();
+        for a in pieces.iter().step_by(5) {
+            for b in pieces.iter().step_by(2) {
+                let rules = [a.clone(), b.clone()];
+                let map = PiecewiseMap::from_pieces(rules.clone());
+                for mask in masks() {
+                    let set = from_mask(mask);
+                    let image = map.apply_set(&set);
+                    assert_normalized(&image);
+                    let expected = IntervalSet::from_ranges(
+                        (0..UNIVERSE)
+                            .filter(|&v| set.contains(v))
+                            .map(|v| brute_apply(&rules, v))
+                            .map(|v| v..v + 1),
+                    );
+                    assert_eq!(image, expected, "{rules:?} {set:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn composes() {
+        let pieces = pieces();
+        for a in &pieces {
+            for b in &pieces {
+                let first = PiecewiseMap::from_pieces([a.clone()]);
+                let second = PiecewiseMap::from_pieces([b.clone()]);
+                let composed = first.then(&second);
+                for value in 0..3 * UNIVERSE {
+                    assert_eq!(
+                        composed.apply(value),
+                        second.apply(first.apply(value)),
+                        "{a:?} then {b:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn composes_near_the_end() {
+        let top = PiecewiseMap::from_pieces([Piece {
+            source: 0..10,
+            destination: u64::MAX - 10,
+        }]);
+        let back = PiecewiseMap::from_pieces([Piece {
+            source: u64::MAX - 5..u64::MAX,
+            destination: 0,
+        }]);
+        let composed = top.then(&back);
+        for value in [0, 4, 5, 9, 10, u64::MAX - 1, u64::MAX] {
+            assert_eq!(composed.apply(value), back.apply(top.apply(value)));
+        }
+        assert_eq!(
+            top.apply_set(&IntervalSet::from_ranges([8..12])).ranges(),
+            [10..12, u64::MAX - 2..u64::MAX]
+        );
+    }
+
+    #[test]
+    fn inverts_maps() {
+        let pieces = pieces();
+        for a in pieces.iter().step_by(5) {
+            for b in pieces.iter().step_by(3) {
+                let rules = [a.clone(), b.clone()];
+                let map = PiecewiseMap::from_pieces(rules.clone());
+                for mask in masks().step_by(7) {
+                    let set = from_mask(mask);
+                    let preimage = map.preimage(&set);
+                    assert_normalized(&preimage);
+                    // values from UNIVERSE on are only sent to themselves.
+                    let expected = IntervalSet::from_ranges(
+                        (0..2 * UNIVERSE)
+                            .filter(|&v| set.contains(brute_apply(&rules, v)))
+                            .map(|v| v..v + 1),
+                    );
+                    assert_eq!(preimage, expected, "{rules:?} {set:?}");
+                    let min = (0..UNIVERSE)
+                        .filter(|&v| set.contains(v))
+                        .map(|v| brute_apply(&rules, v))
+                        .min();
+                    assert_eq!(map.min_over(&set), min, "{rules:?} {set:?}");
+                }
+            }
+        }
+        let top = PiecewiseMap::from_pieces([Piece {
+            source: 0..10,
+            destination: u64::MAX - 10,
+        }]);
+        assert_eq!(
+            top.preimage(&IntervalSet::from_ranges([5..12, u64::MAX - 1..u64::MAX]))
+                .ranges(),
+            [9..12, u64::MAX - 1..u64::MAX]
+        );
+    }
+}
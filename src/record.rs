@@ -0,0 +1,321 @@
+/// Recordings of simulations as terminal animations, to watch how a solution gets to its answer.
+///
+/// Solutions push [`Frame`]s to a [`Recorder`]. A [`Recording`] writes them to an
+/// [asciinema v2](https://docs.asciinema.org/manual/asciicast/v2/) `.cast` file or plays them
+/// in the terminal, while the `()` recorder ignores them: code generic over the recorder pays
+/// nothing when compiled with `()`, not even for drawing the frames.
+use std::fmt::Write as _;
+use std::io::{self, Write as _};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use std::{fs, thread};
+
+use crate::grid::{Grid, Pos};
+use crate::render::Rgb;
+use crate::template::Options;
+
+/// Frames per second used by [`Recording::from_options`] without `--fps`.
+pub const DEFAULT_FPS: f64 = 20.0;
+
+const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub char: char,
+    /// Foreground color, the terminal default if [`None`].
+    pub color: Option<Rgb>,
+}
+
+impl Cell {
+    pub fn new(char: char, color: Rgb) -> Self {
+        Self {
+            char,
+            color: Some(color),
+        }
+    }
+
+    pub fn plain(char: char) -> Self {
+        Self { char, color: None }
+    }
+}
+
+/// A snapshot of a grid as colored characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    pub fn from_fn(width: usize, height: usize, mut cell: impl FnMut(Pos) -> Cell) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(&mut cell)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn from_grid<T>(grid: &Grid<T>, mut cell: impl FnMut(Pos, &T) -> Cell) -> Self {
+        Self::from_fn(grid.width(), grid.height(), |pos| cell(pos, &grid[pos]))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The lines of the frame with 24-bit ANSI colors, only switching colors when they change.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::with_capacity(self.cells.len() * 2);
+        for row in self.cells.chunks(self.width.max(1)) {
+            let mut current = None;
+            for cell in row {
+                if cell.color != current {
+                    match cell.color {
+                        Some(Rgb { r, g, b }) => write!(out, "\x1b[38;2;{r};{g};{b}m").unwrap(),
+                        None => out.push_str("\x1b[0m"),
+                    }
+                    current = cell.color;
+                }
+                out.push(cell.char);
+            }
+            if current.is_some() {
+                out.push_str("\x1b[0m");
+            }
+            out.push_str("\r\n");
+        }
+        out
+    }
+}
+
+/// Receives the frames of a simulation.
+pub trait Recorder {
+    /// Whether frames can be kept at all, for simulations that run another way when watched.
+    const ENABLED: bool = true;
+
+    /// Records the frame drawn by `draw`, which is only called if the frame is kept.
+    fn frame(&mut self, draw: impl FnOnce() -> Frame);
+}
+
+/// Ignores every frame.
+impl Recorder for () {
+    const ENABLED: bool = false;
+
+    #[inline(always)]
+    fn frame(&mut self, _draw: impl FnOnce() -> Frame) {}
+}
+
+impl<R: Recorder> Recorder for Option<R> {
+    const ENABLED: bool = R::ENABLED;
+
+    #[inline]
+    fn frame(&mut self, draw: impl FnOnce() -> Frame) {
+        if let Some(recorder) = self {
+            recorder.frame(draw);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Output {
+    /// An asciinema v2 file, written by [`Recording::finish`].
+    Cast(PathBuf),
+    /// Frames drawn in the terminal as they come, no faster than the frame rate.
+    Play,
+}
+
+#[derive(Debug)]
+pub struct Recording {
+    output: Output,
+    fps: f64,
+    frames: usize,
+    /// Largest frame size so far, the size of the terminal in the cast header.
+    size: (usize, usize),
+    events: String,
+    last_shown: Option<Instant>,
+}
+
+impl Recording {
+    /// # Panics
+    /// If `fps` is not positive.
+    pub fn new(output: Output, fps: f64) -> Self {
+        assert!(fps > 0.0, "the frame rate must be positive");
+        Self {
+            output,
+            fps,
+            frames: 0,
+            size: (0, 0),
+            events: String::new(),
+            last_shown: None,
+        }
+    }
+
+    /// The recording asked for on the command line of the solution binary: `--record <file.cast>`
+    /// or `--play`, at `--fps <n>` frames per second. Problems with the arguments are reported
+    /// on stderr.
+    pub fn from_options(options: &Options) -> Option<Self> {
+        let output = if options.flag("--play") {
+            Output::Play
+        } else if options.flag("--record") {
+            match options
+                .value("--record")
+                .filter(|path| !path.starts_with("--"))
+            {
+                Some(path) => Output::Cast(path.into()),
+                None => {
+                    eprintln!("Missing file after --record");
+                    return None;
+                }
+            }
+        } else {
+            return None;
+        };
+        let fps = match options.value("--fps") {
+            None => DEFAULT_FPS,
+            Some(fps) => match fps.parse() {
+                Ok(fps) if fps > 0.0 => fps,
+                _ => {
+                    eprintln!("Invalid frame rate {fps:?}, expected a positive number");
+                    return None;
+                }
+            },
+        };
+        Some(Self::new(output, fps))
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// The cast file of the frames so far: a JSON header, then one JSON event per frame.
+    pub fn to_cast(&self) -> String {
+        let (width, height) = self.size;
+        format!(
+            "{{\"version\": 2, \"width\": {width}, \"height\": {height}}}\n{}",
+            self.events
+        )
+    }
+
+    /// Writes the cast file, reporting on stderr where it went.
+    pub fn finish(self) {
+        if let Output::Cast(path) = &self.output {
+            match fs::write(path, self.to_cast()) {
+                Ok(()) => eprintln!("Recorded {} frames to {}", self.frames, path.display()),
+                Err(e) => eprintln!("Could not record to {}: {e}", path.display()),
+            }
+        }
+    }
+
+    fn frame_duration(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.fps)
+    }
+}
+
+impl Recorder for Recording {
+    fn frame(&mut self, draw: impl FnOnce() -> Frame) {
+        let frame = draw();
+        let text = format!("{CLEAR_SCREEN}{}", frame.to_ansi());
+        match self.output {
+            Output::Cast(_) => {
+                let time = self.frames as f64 / self.fps;
+                writeln!(self.events, "[{time:.6}, \"o\", {}]", json_string(&text)).unwrap();
+            }
+            Output::Play => {
+                if let Some(last) = self.last_shown {
+                    thread::sleep(self.frame_duration().saturating_sub(last.elapsed()));
+                }
+                let mut stdout = io::stdout().lock();
+                let _ = stdout.write_all(text.as_bytes());
+                let _ = stdout.flush();
+                self.last_shown = Some(Instant::now());
+            }
+        }
+        self.frames += 1;
+        self.size = (self.size.0.max(frame.width), self.size.1.max(frame.height));
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{json_string, Cell, Frame, Output, Recorder, Recording};
+    use crate::render::Rgb;
+    use crate::template::Options;
+
+    fn frame(text: &str) -> Frame {
+        let lines: Vec<&str> = text.lines().collect();
+        Frame::from_fn(lines[0].len(), lines.len(), |(x, y)| {
+            match lines[y].as_bytes()[x] {
+                b'#' => Cell::new('#', Rgb::new(1, 2, 3)),
+                c => Cell::plain(c as char),
+            }
+        })
+    }
+
+    #[test]
+    fn draws_colors_only_when_they_change() {
+        let ansi = frame("##.\n.##\n").to_ansi();
+        assert_eq!(
+            ansi,
+            "\x1b[38;2;1;2;3m##\x1b[0m.\r\n.\x1b[38;2;1;2;3m##\x1b[0m\r\n"
+        );
+    }
+
+    #[test]
+    fn escapes_json() {
+        assert_eq!(json_string("a\"b\\c"), r#""a\"b\\c""#);
+        assert_eq!(json_string("\x1b[0m\r\n"), r#""\u001b[0m\r\n""#);
+        assert_eq!(json_string("é→"), "\"é→\"");
+    }
+
+    #[test]
+    fn writes_casts() {
+        let mut recording = Recording::new(Output::Cast("unused.cast".into()), 4.0);
+        recording.frame(|| frame("..\n"));
+        recording.frame(|| frame("#..\n...\n"));
+        assert_eq!(recording.frames(), 2);
+        let cast = recording.to_cast();
+        let lines: Vec<&str> = cast.lines().collect();
+        assert_eq!(lines[0], r#"{"version": 2, "width": 3, "height": 2}"#);
+        assert_eq!(lines[1], r#"[0.000000, "o", "\u001b[H\u001b[2J..\r\n"]"#);
+        assert!(lines[2].starts_with(r#"[0.250000, "o", "\u001b[H\u001b[2J\u001b[38;2;1;2;3m#"#));
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn skips_drawing_when_disabled() {
+        let mut drawn = 0;
+        let mut draw = || {
+            drawn += 1;
+            frame(".\n")
+        };
+        ().frame(&mut draw);
+        None::<Recording>.frame(&mut draw);
+        Some(Recording::new(Output::Cast("unused.cast".into()), 1.0)).frame(&mut draw);
+        assert_eq!(drawn, 1);
+    }
+}
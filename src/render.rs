@@ -0,0 +1,353 @@
+/// Images of grids, written as PPM or PNG without any image library.
+///
+/// Every cell of a grid becomes a `scale` × `scale` square of the color picked by a closure.
+/// PNG files are stored uncompressed: big, but readable by any viewer.
+use std::path::Path;
+use std::{fs, io};
+
+use crate::grid::{Grid, Pos};
+use crate::template::Options;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb::new(0, 0, 0);
+    pub const WHITE: Rgb = Rgb::new(255, 255, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// A gray of the given lightness.
+    pub const fn gray(level: u8) -> Self {
+        Self::new(level, level, level)
+    }
+
+    /// Mixes `self` into `other` linearly, `t = 0` is `self` and `t = 1` is `other`.
+    pub fn lerp(self, other: Rgb, t: f64) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * t).round() as u8;
+        Self::new(
+            mix(self.r, other.r),
+            mix(self.g, other.g),
+            mix(self.b, other.b),
+        )
+    }
+}
+
+impl From<[u8; 3]> for Rgb {
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        Self::new(r, g, b)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// An image of `width` × `height` cells, each drawn as a `scale` × `scale` square.
+    ///
+    /// # Panics
+    /// If `scale` is zero.
+    pub fn from_fn(
+        width: usize,
+        height: usize,
+        scale: usize,
+        mut color: impl FnMut(Pos) -> Rgb,
+    ) -> Self {
+        assert!(scale > 0, "the scale must be positive");
+        let cells: Vec<Rgb> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(&mut color)
+            .collect();
+        let mut pixels = Vec::with_capacity(cells.len() * scale * scale);
+        for row in cells.chunks(width.max(1)) {
+            let line: Vec<Rgb> = row
+                .iter()
+                .flat_map(|&c| std::iter::repeat_n(c, scale))
+                .collect();
+            for _ in 0..scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        Self {
+            width: width * scale,
+            height: height * scale,
+            pixels,
+        }
+    }
+
+    /// An image of `grid`, colored cell by cell.
+    pub fn from_grid<T>(
+        grid: &Grid<T>,
+        scale: usize,
+        mut color: impl FnMut(Pos, &T) -> Rgb,
+    ) -> Self {
+        Self::from_fn(grid.width(), grid.height(), scale, |pos| {
+            color(pos, &grid[pos])
+        })
+    }
+
+    /// Width in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, (x, y): Pos) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Binary PPM (`P6`).
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.pixels.iter().flat_map(|p| [p.r, p.g, p.b]));
+        out
+    }
+
+    /// 8-bit RGB PNG, with stored (uncompressed) deflate blocks.
+    ///
+    /// # Panics
+    /// If a dimension does not fit the 31 bits PNG allows.
+    pub fn to_png(&self) -> Vec<u8> {
+        let dimension = |d: usize| {
+            u32::try_from(d)
+                .ok()
+                .filter(|&d| d < 1 << 31)
+                .expect("image too large for PNG")
+        };
+        let mut header = Vec::with_capacity(13);
+        header.extend(dimension(self.width).to_be_bytes());
+        header.extend(dimension(self.height).to_be_bytes());
+        // bit depth 8, truecolor, deflate, no filtering, no interlacing.
+        header.extend([8, 2, 0, 0, 0]);
+
+        // each scanline starts with its filter type, 0 for none.
+        let mut raw = Vec::with_capacity(self.height * (1 + 3 * self.width));
+        for line in self.pixels.chunks(self.width.max(1)) {
+            raw.push(0);
+            raw.extend(line.iter().flat_map(|p| [p.r, p.g, p.b]));
+        }
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        write_chunk(&mut out, b"IHDR", &header);
+        write_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        write_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    /// Writes a PNG if `path` ends with `.png`, a PPM otherwise.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let is_png = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+        let bytes = if is_png { self.to_png() } else { self.to_ppm() };
+        fs::write(path, bytes)
+    }
+}
+
+/// Scale used by [`render_requested`] without `--scale`.
+pub const DEFAULT_SCALE: usize = 4;
+
+/// Saves the image drawn by `draw` if the solution binary was run with `--render <path>`,
+/// at the scale passed with `--scale <n>`. `draw` is not called otherwise.
+/// Problems are reported on stderr, as an image is never what the puzzle asks for.
+pub fn render_requested(options: &Options, draw: impl FnOnce(usize) -> Image) {
+    let Some(path) = options.value("--render") else {
+        return;
+    };
+    let scale = match options.value("--scale") {
+        None => DEFAULT_SCALE,
+        Some(scale) => match scale.parse() {
+            Ok(scale) if scale > 0 => scale,
+            _ => {
+                eprintln!("Invalid scale {scale:?}, expected a positive integer");
+                return;
+            }
+        },
+    };
+    let image = draw(scale);
+    match image.save(path) {
+        Ok(()) => eprintln!("Rendered {}x{} image to {path}", image.width, image.height),
+        Err(e) => eprintln!("Could not render to {path}: {e}"),
+    }
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+/// A zlib stream of `data` split into stored deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = u16::MAX as usize;
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK * 5 + 11);
+    // deflate with a 32K window, no preset dictionary, check bits making it a multiple of 31.
+    out.extend([0x78, 0x01]);
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(u8::from(is_final));
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // sums stay below 2^32 over 5552 bytes, the usual bound before reducing.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{adler32, crc32, Image, Rgb};
+    use crate::grid::Grid;
+
+    /// Reads back the pixels of a PNG written by [`Image::to_png`], checking every checksum.
+    fn decode_png(png: &[u8]) -> (usize, usize, Vec<u8>) {
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let mut rest = &png[8..];
+        let mut chunks = Vec::new();
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (body, crc) = (&rest[4..8 + len], &rest[8 + len..12 + len]);
+            assert_eq!(crc32(body).to_be_bytes(), crc);
+            chunks.push((body[..4].to_vec(), body[4..].to_vec()));
+            rest = &rest[12 + len..];
+        }
+        let kinds: Vec<&[u8]> = chunks.iter().map(|(k, _)| k.as_slice()).collect();
+        assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+
+        let header = &chunks[0].1;
+        let width = u32::from_be_bytes(header[..4].try_into().unwrap()) as usize;
+        let height = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+
+        let zlib = &chunks[1].1;
+        assert_eq!(u16::from_be_bytes([zlib[0], zlib[1]]) % 31, 0);
+        let mut deflate = &zlib[2..zlib.len() - 4];
+        let mut raw = Vec::new();
+        loop {
+            let is_final = deflate[0] == 1;
+            let len = u16::from_le_bytes([deflate[1], deflate[2]]);
+            assert_eq!(!len, u16::from_le_bytes([deflate[3], deflate[4]]));
+            raw.extend(&deflate[5..5 + len as usize]);
+            deflate = &deflate[5 + len as usize..];
+            if is_final {
+                break;
+            }
+        }
+        assert!(deflate.is_empty());
+        assert_eq!(adler32(&raw).to_be_bytes(), zlib[zlib.len() - 4..]);
+
+        let pixels = raw
+            .chunks(1 + 3 * width)
+            .flat_map(|line| {
+                assert_eq!(line[0], 0);
+                line[1..].to_vec()
+            })
+            .collect();
+        (width, height, pixels)
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(&vec![255; 100_000]), 0x149a_302c);
+    }
+
+    #[test]
+    fn scales_cells() {
+        let grid = Grid::parse("#.\n..\n.#\n", |c| c == '#').unwrap();
+        let image = Image::from_grid(
+            &grid,
+            3,
+            |_, &wall| if wall { Rgb::BLACK } else { Rgb::WHITE },
+        );
+        assert_eq!((image.width(), image.height()), (6, 9));
+        assert_eq!(image.pixel((2, 2)), Rgb::BLACK);
+        assert_eq!(image.pixel((3, 2)), Rgb::WHITE);
+        assert_eq!(image.pixel((5, 8)), Rgb::BLACK);
+
+        let ppm = image.to_ppm();
+        assert!(ppm.starts_with(b"P6\n6 9\n255\n"));
+        assert_eq!(ppm.len(), 11 + 6 * 9 * 3);
+    }
+
+    #[test]
+    fn writes_readable_pngs() {
+        let color = |(x, y): (usize, usize)| Rgb::new(x as u8, y as u8, (x ^ y) as u8);
+        // large enough to need several deflate blocks.
+        for (width, height) in [(0, 0), (1, 1), (7, 3), (200, 150)] {
+            let image = Image::from_fn(width, height, 1, color);
+            let (w, h, pixels) = decode_png(&image.to_png());
+            assert_eq!((w, h), (width, height));
+            let expected: Vec<u8> = (0..height)
+                .flat_map(|y| {
+                    (0..width).flat_map(move |x| {
+                        let c = color((x, y));
+                        [c.r, c.g, c.b]
+                    })
+                })
+                .collect();
+            assert_eq!(pixels, expected);
+        }
+    }
+
+    #[test]
+    fn mixes_colors() {
+        let (a, b) = (Rgb::new(0, 100, 200), Rgb::new(100, 100, 0));
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.5), Rgb::new(50, 100, 100));
+        assert_eq!(a.lerp(b, 7.0), b);
+    }
+}
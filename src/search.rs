@@ -0,0 +1,430 @@
+/// Breadth-first search, Dijkstra and A* over any state type.
+///
+/// The graph is never built: it is explored through a `neighbours` closure returning the states
+/// reachable from a given one, along with the cost of the move for the weighted searches.
+/// Every search stops at the first state accepted by `is_goal` (pass `|_| false` to explore
+/// everything reachable) and returns the [`Visited`] states, from which paths can be rebuilt.
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt::{self, Display};
+use std::hash::Hash;
+
+/// Counters describing how much work a search did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Distinct states reached.
+    pub reached: usize,
+    /// States whose neighbours were explored.
+    pub expanded: usize,
+    /// Queue insertions, re-insertions of improved states included.
+    pub pushed: usize,
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} reached, {} expanded, {} pushed",
+            self.reached, self.expanded, self.pushed
+        )
+    }
+}
+
+struct Node<S> {
+    state: S,
+    cost: usize,
+    parent: Option<usize>,
+    expanded: bool,
+}
+
+/// The states reached by a search, with the best cost found for each of them.
+pub struct Visited<S> {
+    nodes: Vec<Node<S>>,
+    index: HashMap<S, usize>,
+    goal: Option<usize>,
+    stats: Stats,
+}
+
+impl<S: Clone + Eq + Hash> Visited<S> {
+    fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            goal: None,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Records that `state` can be reached for `cost`, returns its index if that is an improvement.
+    fn reach(&mut self, state: S, cost: usize, parent: Option<usize>) -> Option<usize> {
+        match self.index.entry(state) {
+            Entry::Vacant(entry) => {
+                let i = self.nodes.len();
+                self.nodes.push(Node {
+                    state: entry.key().clone(),
+                    cost,
+                    parent,
+                    expanded: false,
+                });
+                entry.insert(i);
+                Some(i)
+            }
+            Entry::Occupied(entry) => {
+                let node = &mut self.nodes[*entry.get()];
+                if node.expanded || node.cost <= cost {
+                    return None;
+                }
+                node.cost = cost;
+                node.parent = parent;
+                Some(*entry.get())
+            }
+        }
+    }
+
+    /// The goal state the search stopped at.
+    pub fn goal(&self) -> Option<&S> {
+        self.goal.map(|i| &self.nodes[i].state)
+    }
+
+    /// Cost of the goal state.
+    pub fn cost(&self) -> Option<usize> {
+        self.goal.map(|i| self.nodes[i].cost)
+    }
+
+    /// States from a start to the goal, both included.
+    pub fn path(&self) -> Option<Vec<S>> {
+        self.goal.map(|i| self.path_from_index(i))
+    }
+
+    /// Best cost found for `state`. It is only known to be optimal if the search went past it,
+    /// which is always the case when it explored everything.
+    pub fn cost_to(&self, state: &S) -> Option<usize> {
+        self.index.get(state).map(|&i| self.nodes[i].cost)
+    }
+
+    /// States from a start to `state`, both included, see [`Visited::cost_to`].
+    pub fn path_to(&self, state: &S) -> Option<Vec<S>> {
+        self.index.get(state).map(|&i| self.path_from_index(i))
+    }
+
+    fn path_from_index(&self, mut i: usize) -> Vec<S> {
+        let mut path = vec![self.nodes[i].state.clone()];
+        while let Some(parent) = self.nodes[i].parent {
+            path.push(self.nodes[parent].state.clone());
+            i = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Every reached state with its best cost found, in the order they were reached.
+    pub fn iter(&self) -> impl Iterator<Item = (&S, usize)> {
+        self.nodes.iter().map(|node| (&node.state, node.cost))
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            reached: self.nodes.len(),
+            ..self.stats
+        }
+    }
+}
+
+/// Queue of node indices by priority.
+trait Queue {
+    fn push(&mut self, priority: usize, node: usize);
+    fn pop(&mut self) -> Option<usize>;
+}
+
+struct Fifo(VecDeque<usize>);
+
+impl Queue for Fifo {
+    fn push(&mut self, _priority: usize, node: usize) {
+        self.0.push_back(node);
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        self.0.pop_front()
+    }
+}
+
+struct Heap(BinaryHeap<Reverse<(usize, usize)>>);
+
+impl Queue for Heap {
+    fn push(&mut self, priority: usize, node: usize) {
+        self.0.push(Reverse((priority, node)));
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        self.0.pop().map(|Reverse((_, node))| node)
+    }
+}
+
+/// One bucket per priority, scanned upwards: priorities never go below the last popped one.
+struct Buckets {
+    buckets: Vec<Vec<usize>>,
+    current: usize,
+    len: usize,
+}
+
+impl Queue for Buckets {
+    fn push(&mut self, priority: usize, node: usize) {
+        if priority >= self.buckets.len() {
+            self.buckets.resize_with(priority + 1, Vec::new);
+        }
+        self.buckets[priority].push(node);
+        self.current = self.current.min(priority);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+        while self.buckets[self.current].is_empty() {
+            self.current += 1;
+        }
+        self.len -= 1;
+        self.buckets[self.current].pop()
+    }
+}
+
+fn run<S, I>(
+    mut queue: impl Queue,
+    starts: impl IntoIterator<Item = S>,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> usize,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Visited<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    let mut visited = Visited::new();
+    for start in starts {
+        let priority = heuristic(&start);
+        if let Some(i) = visited.reach(start, 0, None) {
+            queue.push(priority, i);
+            visited.stats.pushed += 1;
+        }
+    }
+
+    while let Some(i) = queue.pop() {
+        let node = &mut visited.nodes[i];
+        if node.expanded {
+            // an outdated entry, the state was improved and expanded since.
+            continue;
+        }
+        node.expanded = true;
+        let (state, cost) = (node.state.clone(), node.cost);
+        visited.stats.expanded += 1;
+        if is_goal(&state) {
+            visited.goal = Some(i);
+            break;
+        }
+        for (next, step) in neighbours(&state) {
+            let next_cost = cost + step;
+            let priority = next_cost + heuristic(&next);
+            if let Some(j) = visited.reach(next, next_cost, Some(i)) {
+                queue.push(priority, j);
+                visited.stats.pushed += 1;
+            }
+        }
+    }
+    visited
+}
+
+/// Breadth-first search, every move costs 1.
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut neighbours: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Visited<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    run(
+        Fifo(VecDeque::new()),
+        starts,
+        |state| neighbours(state).into_iter().map(|next| (next, 1)),
+        |_| 0,
+        is_goal,
+    )
+}
+
+/// Dijkstra with a binary heap.
+pub fn dijkstra<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    neighbours: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Visited<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    run(Heap(BinaryHeap::new()), starts, neighbours, |_| 0, is_goal)
+}
+
+/// Dijkstra with a bucket queue, faster than the heap when costs stay small,
+/// as it needs one bucket per possible cost.
+pub fn dijkstra_buckets<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    neighbours: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Visited<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    let queue = Buckets {
+        buckets: Vec::new(),
+        current: 0,
+        len: 0,
+    };
+    run(queue, starts, neighbours, |_| 0, is_goal)
+}
+
+/// A* with a binary heap. `heuristic` must never overestimate the remaining cost to a goal,
+/// nor decrease by more than the cost of a move, or the result may not be optimal.
+pub fn astar<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    neighbours: impl FnMut(&S) -> I,
+    heuristic: impl FnMut(&S) -> usize,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Visited<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, usize)>,
+{
+    run(
+        Heap(BinaryHeap::new()),
+        starts,
+        neighbours,
+        heuristic,
+        is_goal,
+    )
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{astar, bfs, dijkstra, dijkstra_buckets, Visited};
+    use crate::grid::{Grid, Pos};
+    use crate::random::Rng;
+
+    fn neighbours(grid: &Grid<u8>, pos: Pos) -> Vec<(Pos, usize)> {
+        grid.neighbours_4(pos)
+            .filter(|&next| grid[next] != 0)
+            .map(|next| (next, grid[next] as usize))
+            .collect()
+    }
+
+    /// Bellman-Ford like relaxation until nothing changes.
+    fn brute_costs(grid: &Grid<u8>) -> Grid<Option<usize>> {
+        let mut costs = Grid::new(grid.width(), grid.height(), None);
+        costs[(0, 0)] = Some(0);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for pos in grid.positions() {
+                let Some(cost) = costs[pos] else { continue };
+                for (next, step) in neighbours(grid, pos) {
+                    if costs[next].is_none_or(|c| cost + step < c) {
+                        costs[next] = Some(cost + step);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        costs
+    }
+
+    fn random_grid(rng: &mut Rng) -> Grid<u8> {
+        let (width, height) = (rng.range_usize(1, 8), rng.range_usize(1, 8));
+        let mut grid = Grid::from_fn(width, height, |_| {
+            if rng.chance(1, 5) {
+                0
+            } else {
+                rng.range(1, 10) as u8
+            }
+        });
+        grid[(0, 0)] = 1;
+        grid
+    }
+
+    fn check_path(grid: &Grid<u8>, visited: &Visited<Pos>, goal: Pos) {
+        let path = visited.path_to(&goal).unwrap();
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&goal));
+        let cost: usize = path[1..].iter().map(|&pos| grid[pos] as usize).sum();
+        assert_eq!(Some(cost), visited.cost_to(&goal));
+    }
+
+    #[test]
+    fn weighted_searches_match_relaxation() {
+        let mut rng = Rng::new(17);
+        for _ in 0..300 {
+            let grid = random_grid(&mut rng);
+            let expected = brute_costs(&grid);
+            let goal = grid.max_pos();
+
+            let everything = dijkstra([(0, 0)], |&pos| neighbours(&grid, pos), |_| false);
+            let buckets = dijkstra_buckets([(0, 0)], |&pos| neighbours(&grid, pos), |_| false);
+            for pos in grid.positions() {
+                assert_eq!(everything.cost_to(&pos), expected[pos]);
+                assert_eq!(buckets.cost_to(&pos), expected[pos]);
+            }
+            assert_eq!(everything.goal(), None);
+
+            let manhattan = |&(x, y): &Pos| (goal.0 - x) + (goal.1 - y);
+            for visited in [
+                dijkstra([(0, 0)], |&pos| neighbours(&grid, pos), |&pos| pos == goal),
+                dijkstra_buckets([(0, 0)], |&pos| neighbours(&grid, pos), |&pos| pos == goal),
+                astar(
+                    [(0, 0)],
+                    |&pos| neighbours(&grid, pos),
+                    manhattan,
+                    |&pos| pos == goal,
+                ),
+            ] {
+                assert_eq!(visited.cost(), expected[goal]);
+                if expected[goal].is_some() {
+                    check_path(&grid, &visited, goal);
+                    assert_eq!(visited.goal(), Some(&goal));
+                    assert!(visited.stats().expanded <= everything.stats().expanded);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bfs_counts_moves() {
+        let grid = Grid::parse("..#.\n.##.\n....", |c| c == '.').unwrap();
+        let visited = bfs(
+            [(0, 0)],
+            |&pos| {
+                grid.neighbours_4(pos)
+                    .filter(|&next| grid[next])
+                    .collect::<Vec<_>>()
+            },
+            |&pos| pos == (3, 0),
+        );
+        assert_eq!(visited.cost(), Some(7));
+        assert_eq!(visited.path().unwrap().len(), 8);
+        assert_eq!(visited.cost_to(&(2, 0)), None);
+
+        let everything = bfs(
+            [(0, 0), (3, 0)],
+            |&pos| {
+                grid.neighbours_4(pos)
+                    .filter(|&next| grid[next])
+                    .collect::<Vec<_>>()
+            },
+            |_| false,
+        );
+        assert_eq!(everything.iter().map(|(_, cost)| cost).max(), Some(3));
+        let stats = everything.stats();
+        assert_eq!((stats.reached, stats.expanded), (9, 9));
+    }
+}
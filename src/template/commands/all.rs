@@ -63,7 +63,7 @@ pub fn get_path_for_bin(day: Day) -> String {
 
 /// All solutions live in isolated binaries.
 /// This module encapsulates interaction with these binaries, both invoking them as well as parsing the timing output.
-mod child_commands {
+pub(crate) mod child_commands {
     use super::{get_path_for_bin, Error};
     use crate::Day;
     use std::{
@@ -165,13 +165,13 @@ mod child_commands {
         s.split(postfix).next()?.parse().ok()
     }
 
-    fn parse_time(line: &str) -> Option<(&str, f64)> {
+    pub fn parse_time(line: &str) -> Option<(&str, f64)> {
         // for possible time formats, see: https://github.com/rust-lang/rust/blob/1.64.0/library/core/src/time.rs#L1176-L1200
         let str_timing = line
             .split(" samples)")
             .next()?
             .split('(')
-            .last()?
+            .next_back()?
             .split('@')
             .next()?
             .trim();
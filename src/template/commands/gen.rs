@@ -0,0 +1,21 @@
+use std::process;
+
+use crate::generate;
+use crate::Day;
+
+pub fn handle(day: Day, size: usize, seed: u64) {
+    match generate::write(day, size, seed) {
+        Ok(path) => {
+            println!(
+                "Generated input of size {size} (seed {seed}) to \"{}\".",
+                path.display()
+            );
+            println!("---");
+            println!("🎄 Type `cargo solve {day}` with this file as \"data/inputs/{day}.txt\" to run it.");
+        }
+        Err(e) => {
+            eprintln!("Failed to generate input: {e}");
+            process::exit(1);
+        }
+    }
+}
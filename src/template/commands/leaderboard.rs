@@ -0,0 +1,90 @@
+use std::process;
+
+use crate::template::leaderboard::{self, format_elapsed, format_timestamp, Leaderboard};
+use crate::template::{ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+pub fn handle(path: &str) {
+    let leaderboard = match leaderboard::read(path) {
+        Ok(leaderboard) => leaderboard,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    };
+
+    print_ranking(&leaderboard);
+    println!();
+    print_fastest(&leaderboard);
+    println!();
+    print_members(&leaderboard);
+}
+
+fn print_ranking(leaderboard: &Leaderboard) {
+    println!(
+        "{ANSI_BOLD}Private leaderboard {} (owner #{}){ANSI_RESET}",
+        leaderboard.event, leaderboard.owner_id
+    );
+    println!("------");
+
+    for (rank, member) in leaderboard.members.iter().enumerate() {
+        println!(
+            "{:>3}) {:>5} {:>3}⭐ {}",
+            rank + 1,
+            member.local_score,
+            member.stars,
+            member.display_name()
+        );
+    }
+}
+
+fn print_fastest(leaderboard: &Leaderboard) {
+    println!("{ANSI_BOLD}Fastest solvers{ANSI_RESET} {ANSI_ITALIC}(time since unlock){ANSI_RESET}");
+    println!("------");
+
+    let days = leaderboard
+        .members
+        .iter()
+        .flat_map(|m| m.days.keys().copied())
+        .collect::<std::collections::BTreeSet<_>>();
+
+    for day in days {
+        let fastest = |part| {
+            leaderboard.fastest(day, part).map_or_else(
+                || "-".to_string(),
+                |(member, elapsed)| {
+                    format!("{} ({})", member.display_name(), format_elapsed(elapsed))
+                },
+            )
+        };
+        println!("Day {day}: Part 1: {} | Part 2: {}", fastest(1), fastest(2));
+    }
+}
+
+fn print_members(leaderboard: &Leaderboard) {
+    for (index, member) in leaderboard.members.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+
+        println!("{ANSI_BOLD}{}{ANSI_RESET}", member.display_name());
+        println!("------");
+
+        if member.days.is_empty() {
+            println!("No stars.");
+            continue;
+        }
+
+        for (day, stars) in &member.days {
+            let part_2 = stars
+                .part_2
+                .map_or_else(|| "-".to_string(), format_timestamp);
+            let delta = stars
+                .delta()
+                .map_or_else(|| "-".to_string(), format_elapsed);
+            println!(
+                "Day {day}: Part 1: {} | Part 2: {part_2} | Δ {delta}",
+                format_timestamp(stars.part_1)
+            );
+        }
+    }
+}
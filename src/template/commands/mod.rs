@@ -1,5 +1,8 @@
 pub mod all;
 pub mod download;
+pub mod gen;
+pub mod leaderboard;
 pub mod read;
 pub mod scaffold;
+pub mod scale;
 pub mod solve;
@@ -0,0 +1,336 @@
+/// Empirical scaling of a solution: runs it on inputs of growing size, fits `time ≈ c·n^k` where `n` is
+/// the size of the input in bytes, and plots the timings on a log-log scale.
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::Duration;
+use std::{fs, io};
+
+use crate::generate;
+use crate::template::commands::all::{child_commands::parse_time, get_path_for_bin};
+use crate::template::{ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+use crate::Day;
+
+/// Sizes used when neither sizes nor inputs are passed, see [`generate::generate`] for their meaning.
+pub const DEFAULT_SIZES: [usize; 9] = [16, 32, 64, 128, 256, 512, 1024, 2048, 4096];
+
+/// Generated sizes stop growing once a part takes longer than this.
+const TIME_BUDGET: Duration = Duration::from_secs(2);
+
+/// Fast runs are noisy, they are repeated and the fastest one is kept.
+const REPEAT_BELOW: Duration = Duration::from_millis(50);
+const REPEATS: usize = 3;
+
+const PLOT_WIDTH: usize = 60;
+const PLOT_HEIGHT: usize = 15;
+
+struct Measurement {
+    bytes: usize,
+    parts: [Option<Duration>; 2],
+}
+
+pub fn handle(day: Day, sizes: Option<Vec<usize>>, inputs: Vec<PathBuf>, seed: u64, release: bool) {
+    if !Path::new(&get_path_for_bin(day)).exists() {
+        eprintln!("Day {day} is not solved yet.");
+        process::exit(1);
+    }
+
+    println!("{ANSI_BOLD}Day {day} scaling{ANSI_RESET}");
+    println!("------");
+    println!(
+        "{:>12} {:>12} {:>12} {:>12}",
+        "input", "bytes", "part 1", "part 2"
+    );
+
+    let result = if inputs.is_empty() {
+        measure_generated(day, &sizes.unwrap_or(DEFAULT_SIZES.to_vec()), seed, release)
+    } else {
+        measure_inputs(day, &inputs, release)
+    };
+    let measurements = match result {
+        Ok(measurements) => measurements,
+        Err(e) => {
+            eprintln!("Failed to run day {day}: {e}");
+            process::exit(1);
+        }
+    };
+
+    println!();
+    let series: Vec<(char, Vec<(f64, f64)>)> = ['1', '2']
+        .into_iter()
+        .enumerate()
+        .map(|(part, mark)| {
+            let points = measurements
+                .iter()
+                .filter_map(|m| Some((m.bytes as f64, m.parts[part]?.as_nanos() as f64)))
+                .collect();
+            (mark, points)
+        })
+        .collect();
+
+    for (mark, points) in &series {
+        match fit_exponent(points) {
+            Some(k) => println!(
+                "Part {mark}: time ~ n^{k:.2} {ANSI_ITALIC}({}){ANSI_RESET}",
+                describe_exponent(k)
+            ),
+            None => {
+                println!("Part {mark}: not enough distinct input sizes to fit a growth exponent.")
+            }
+        }
+    }
+
+    println!();
+    for line in plot(&series, PLOT_WIDTH, PLOT_HEIGHT) {
+        println!("{line}");
+    }
+}
+
+fn measure_generated(
+    day: Day,
+    sizes: &[usize],
+    seed: u64,
+    release: bool,
+) -> Result<Vec<Measurement>, io::Error> {
+    let mut measurements = Vec::new();
+    for &size in sizes {
+        let path = generate::write(day, size, seed)?;
+        let measurement = measure(day, &size.to_string(), &path, release)?;
+        let exceeded = measurement
+            .parts
+            .iter()
+            .flatten()
+            .any(|&duration| duration > TIME_BUDGET);
+        measurements.push(measurement);
+        if exceeded {
+            println!(
+                "{ANSI_ITALIC}Stopping here, a part took more than {TIME_BUDGET:?}.{ANSI_RESET}"
+            );
+            break;
+        }
+    }
+    Ok(measurements)
+}
+
+fn measure_inputs(
+    day: Day,
+    inputs: &[PathBuf],
+    release: bool,
+) -> Result<Vec<Measurement>, io::Error> {
+    let mut inputs = inputs
+        .iter()
+        .map(|path| Ok((fs::metadata(path)?.len(), path)))
+        .collect::<Result<Vec<_>, io::Error>>()?;
+    inputs.sort_unstable();
+
+    inputs
+        .into_iter()
+        .map(|(_, path)| {
+            let label = path.file_name().map_or_else(
+                || path.display().to_string(),
+                |name| name.to_string_lossy().into(),
+            );
+            measure(day, &label, path, release)
+        })
+        .collect()
+}
+
+/// Runs the solution binary on `path` and prints the timings of both parts.
+fn measure(day: Day, label: &str, path: &Path, release: bool) -> Result<Measurement, io::Error> {
+    let bytes = usize::try_from(fs::metadata(path)?.len()).unwrap_or(usize::MAX);
+    let mut parts = run_solution(day, path, release)?;
+    for _ in 1..REPEATS {
+        if parts
+            .iter()
+            .flatten()
+            .any(|&duration| duration > REPEAT_BELOW)
+        {
+            break;
+        }
+        let again = run_solution(day, path, release)?;
+        for (part, duration) in parts.iter_mut().zip(again) {
+            *part = match (*part, duration) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        }
+    }
+
+    let format = |part: Option<Duration>| part.map_or("✖".into(), |d| format!("{d:.1?}"));
+    println!(
+        "{label:>12} {bytes:>12} {:>12} {:>12}",
+        format(parts[0]),
+        format(parts[1])
+    );
+
+    Ok(Measurement { bytes, parts })
+}
+
+fn run_solution(day: Day, path: &Path, release: bool) -> Result<[Option<Duration>; 2], io::Error> {
+    let day_padded = day.to_string();
+    let mut args = vec!["run", "--quiet", "--bin", &day_padded];
+    if release {
+        args.push("--release");
+    }
+    let path = path.to_string_lossy();
+    args.extend(["--", "--input", &path]);
+
+    let output = Command::new("cargo").args(&args).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    let mut parts = [None, None];
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        // intermediate results are overwritten with `\r`, only keep the final one.
+        let line = line.rsplit('\r').next().unwrap_or(line);
+        let Some((_, nanos)) = parse_time(line) else {
+            continue;
+        };
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let duration = Duration::from_nanos(nanos as u64);
+        if line.starts_with("Part 1") {
+            parts[0] = Some(duration);
+        } else if line.starts_with("Part 2") {
+            parts[1] = Some(duration);
+        }
+    }
+    Ok(parts)
+}
+
+/// Least squares fit of `ln(y) = k·ln(x) + c`, returns `k`.
+/// Returns [`None`] without at least two distinct `x`.
+fn fit_exponent(points: &[(f64, f64)]) -> Option<f64> {
+    let logs: Vec<(f64, f64)> = points
+        .iter()
+        .filter(|&&(x, y)| x > 0.0 && y > 0.0)
+        .map(|&(x, y)| (x.ln(), y.ln()))
+        .collect();
+    let n = logs.len() as f64;
+    let mean_x = logs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = logs.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = logs.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let sxy: f64 = logs.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    (sxx > 1e-9).then(|| sxy / sxx)
+}
+
+fn describe_exponent(k: f64) -> &'static str {
+    match k {
+        k if k < 0.7 => "sublinear, probably dominated by overhead",
+        k if k < 1.4 => "about linear",
+        k if k < 1.7 => "between linear and quadratic",
+        k if k < 2.4 => "about quadratic",
+        _ => "cubic or worse",
+    }
+}
+
+/// Plots series of `(x, y)` points on a log-log scale, every series with its own mark
+/// (`*` where they overlap). Returns the lines of the plot, axes and labels included.
+fn plot(series: &[(char, Vec<(f64, f64)>)], width: usize, height: usize) -> Vec<String> {
+    let points = || series.iter().flat_map(|(_, points)| points.iter());
+    let bounds = |coordinate: fn(&(f64, f64)) -> f64| {
+        points()
+            .map(coordinate)
+            .filter(|&v| v > 0.0)
+            .fold(None, |bounds: Option<(f64, f64)>, v| {
+                Some(bounds.map_or((v, v), |(low, high)| (low.min(v), high.max(v))))
+            })
+    };
+    let (Some((min_x, max_x)), Some((min_y, max_y))) = (bounds(|p| p.0), bounds(|p| p.1)) else {
+        return vec!["(nothing to plot)".into()];
+    };
+
+    let scale = |v: f64, min: f64, max: f64, cells: usize| {
+        if max <= min {
+            return 0;
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let cell =
+            ((v.ln() - min.ln()) / (max.ln() - min.ln()) * (cells - 1) as f64).round() as usize;
+        cell.min(cells - 1)
+    };
+
+    let mut canvas = vec![vec![' '; width]; height];
+    for (mark, points) in series {
+        for &(x, y) in points.iter().filter(|&&(x, y)| x > 0.0 && y > 0.0) {
+            let cell = &mut canvas[height - 1 - scale(y, min_y, max_y, height)]
+                [scale(x, min_x, max_x, width)];
+            *cell = if *cell == ' ' || *cell == *mark {
+                *mark
+            } else {
+                '*'
+            };
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let time_label = |nanos: f64| format!("{:.1?}", Duration::from_nanos(nanos as u64));
+    let label_width = time_label(max_y).len().max(time_label(min_y).len());
+    let mut lines: Vec<String> = canvas
+        .into_iter()
+        .enumerate()
+        .map(|(row, cells)| {
+            let label = match row {
+                0 => time_label(max_y),
+                r if r == height - 1 => time_label(min_y),
+                _ => String::new(),
+            };
+            format!(
+                "{label:>label_width$} │{}",
+                cells.into_iter().collect::<String>()
+            )
+        })
+        .collect();
+    lines.push(format!("{:>label_width$} └{}", "", "─".repeat(width)));
+    let (min_label, max_label) = (format!("{min_x} B"), format!("{max_x} B"));
+    lines.push(format!(
+        "{:>label_width$}  {min_label}{max_label:>pad$}",
+        "",
+        pad = width.saturating_sub(min_label.len())
+    ));
+    lines
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{fit_exponent, plot};
+
+    #[test]
+    fn fits_power_laws() {
+        let quadratic: Vec<(f64, f64)> = [10.0, 20.0, 40.0, 80.0]
+            .iter()
+            .map(|&n: &f64| (n, 3.0 * n * n))
+            .collect();
+        assert!((fit_exponent(&quadratic).unwrap() - 2.0).abs() < 1e-9);
+
+        let linear = [(100.0, 1.0), (1000.0, 10.0)];
+        assert!((fit_exponent(&linear).unwrap() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn needs_distinct_sizes() {
+        assert_eq!(fit_exponent(&[]), None);
+        assert_eq!(fit_exponent(&[(10.0, 1.0), (10.0, 2.0)]), None);
+    }
+
+    #[test]
+    fn plots_on_log_scale() {
+        let lines = plot(
+            &[
+                (
+                    '1',
+                    vec![(10.0, 1_000.0), (100.0, 10_000.0), (1000.0, 100_000.0)],
+                ),
+                ('2', vec![(10.0, 1_000.0)]),
+            ],
+            11,
+            3,
+        );
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].ends_with("│          1"), "{lines:?}");
+        assert!(lines[1].ends_with("│     1     "), "{lines:?}");
+        assert!(lines[2].ends_with("│*          "), "{lines:?}");
+        assert!(lines[4].contains("10 B") && lines[4].ends_with("1000 B"));
+    }
+}
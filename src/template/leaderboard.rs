@@ -0,0 +1,464 @@
+/// Module that reads the private leaderboard JSON exported by the Advent of Code website.
+/// The JSON is available at `https://adventofcode.com/{year}/leaderboard/private/view/{id}.json`.
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::{fs, io};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while_m_n},
+    character::complete::{anychar, char, multispace0, none_of},
+    combinator::{map, map_opt, map_res, value},
+    multi::{fold_many0, separated_list0},
+    number::complete::double,
+    sequence::{delimited, preceded, separated_pair},
+    IResult,
+};
+
+use crate::Day;
+
+#[derive(Debug)]
+pub enum Error {
+    Parser(String),
+    IO(io::Error),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::IO(e)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parser(msg) => write!(f, "could not parse leaderboard: {msg}"),
+            Error::IO(e) => write!(f, "could not read leaderboard: {e}"),
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Minimal JSON document model, enough to walk the leaderboard export.
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(BTreeMap<String, Json>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(o) => o.get(key),
+            _ => None,
+        }
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Json::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Some(*n as u64),
+            // the website serializes some ids and the event year as strings.
+            Json::String(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn as_object(&self) -> Option<&BTreeMap<String, Json>> {
+        match self {
+            Json::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+}
+
+fn parse_json_string(input: &str) -> IResult<&str, String> {
+    let unicode_escape = map_opt(
+        map_res(
+            preceded(
+                char('u'),
+                take_while_m_n(4, 4, |c: char| c.is_ascii_hexdigit()),
+            ),
+            |hex| u32::from_str_radix(hex, 16),
+        ),
+        char::from_u32,
+    );
+    let escaped = preceded(
+        char('\\'),
+        alt((
+            unicode_escape,
+            map_opt(anychar, |c| match c {
+                '"' | '\\' | '/' => Some(c),
+                'n' => Some('\n'),
+                't' => Some('\t'),
+                'r' => Some('\r'),
+                'b' => Some('\u{8}'),
+                'f' => Some('\u{c}'),
+                _ => None,
+            }),
+        )),
+    );
+    delimited(
+        char('"'),
+        fold_many0(alt((escaped, none_of("\"\\"))), String::new, |mut s, c| {
+            s.push(c);
+            s
+        }),
+        char('"'),
+    )(input)
+}
+
+fn parse_json_value(input: &str) -> IResult<&str, Json> {
+    delimited(
+        multispace0,
+        alt((
+            value(Json::Null, tag("null")),
+            value(Json::Bool(true), tag("true")),
+            value(Json::Bool(false), tag("false")),
+            map(double, Json::Number),
+            map(parse_json_string, Json::String),
+            map(
+                delimited(
+                    char('['),
+                    separated_list0(char(','), parse_json_value),
+                    preceded(multispace0, char(']')),
+                ),
+                Json::Array,
+            ),
+            map(
+                delimited(
+                    char('{'),
+                    separated_list0(
+                        char(','),
+                        separated_pair(
+                            delimited(multispace0, parse_json_string, multispace0),
+                            char(':'),
+                            parse_json_value,
+                        ),
+                    ),
+                    preceded(multispace0, char('}')),
+                ),
+                |entries| Json::Object(entries.into_iter().collect()),
+            ),
+        )),
+        multispace0,
+    )(input)
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// The stars a member collected on a given day, as unix timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayStars {
+    pub part_1: u64,
+    pub part_2: Option<u64>,
+}
+
+impl DayStars {
+    /// Seconds spent between the first and the second star of the day.
+    #[must_use]
+    pub fn delta(&self) -> Option<u64> {
+        self.part_2.map(|ts| ts.saturating_sub(self.part_1))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub id: u64,
+    pub name: Option<String>,
+    pub local_score: u64,
+    pub stars: u64,
+    pub days: BTreeMap<Day, DayStars>,
+}
+
+impl Member {
+    /// Anonymous members are displayed the same way the website does.
+    #[must_use]
+    pub fn display_name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("(anonymous user #{})", self.id))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Leaderboard {
+    pub event: u16,
+    pub owner_id: u64,
+    pub members: Vec<Member>,
+}
+
+fn parse_member(json: &Json) -> Result<Member, Error> {
+    let field = |key: &str| {
+        json.get(key)
+            .and_then(Json::as_u64)
+            .ok_or_else(|| Error::Parser(format!("member is missing field \"{key}\".")))
+    };
+
+    let mut days = BTreeMap::new();
+
+    if let Some(completion) = json.get("completion_day_level").and_then(Json::as_object) {
+        for (day, levels) in completion {
+            let day: Day = day
+                .parse()
+                .map_err(|_| Error::Parser(format!("invalid day \"{day}\".")))?;
+            let star_ts = |part: &str| {
+                levels
+                    .get(part)
+                    .and_then(|level| level.get("get_star_ts"))
+                    .and_then(Json::as_u64)
+            };
+            let Some(part_1) = star_ts("1") else {
+                return Err(Error::Parser(format!("day {day} has no first star.")));
+            };
+            days.insert(
+                day,
+                DayStars {
+                    part_1,
+                    part_2: star_ts("2"),
+                },
+            );
+        }
+    }
+
+    Ok(Member {
+        id: field("id")?,
+        name: json.get("name").and_then(Json::as_str).map(String::from),
+        local_score: field("local_score")?,
+        stars: field("stars")?,
+        days,
+    })
+}
+
+impl TryFrom<&str> for Leaderboard {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (left, json) =
+            parse_json_value(value).map_err(|e| Error::Parser(format!("invalid JSON: {e}")))?;
+
+        if !left.is_empty() {
+            return Err(Error::Parser("trailing characters after JSON.".into()));
+        }
+
+        let event = json
+            .get("event")
+            .and_then(Json::as_u64)
+            .and_then(|e| u16::try_from(e).ok())
+            .ok_or_else(|| Error::Parser("missing field \"event\".".into()))?;
+
+        let owner_id = json
+            .get("owner_id")
+            .and_then(Json::as_u64)
+            .ok_or_else(|| Error::Parser("missing field \"owner_id\".".into()))?;
+
+        let mut members = json
+            .get("members")
+            .and_then(Json::as_object)
+            .ok_or_else(|| Error::Parser("missing field \"members\".".into()))?
+            .values()
+            .map(parse_member)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // same ordering as the website: local score, then stars, then oldest account first.
+        members.sort_by(|a, b| {
+            b.local_score
+                .cmp(&a.local_score)
+                .then(b.stars.cmp(&a.stars))
+                .then(a.id.cmp(&b.id))
+        });
+
+        Ok(Leaderboard {
+            event,
+            owner_id,
+            members,
+        })
+    }
+}
+
+impl Leaderboard {
+    /// Unix timestamp at which the puzzle of `day` was released (midnight EST).
+    #[must_use]
+    pub fn unlock_ts(&self, day: Day) -> u64 {
+        const UNLOCK_HOUR_UTC: u64 = 5;
+        days_from_civil(u64::from(self.event), 12, u64::from(day.into_inner())) * 86400
+            + UNLOCK_HOUR_UTC * 3600
+    }
+
+    /// The fastest member for a given day and part, as `(member, seconds since unlock)`.
+    #[must_use]
+    pub fn fastest(&self, day: Day, part: u8) -> Option<(&Member, u64)> {
+        self.members
+            .iter()
+            .filter_map(|m| {
+                let stars = m.days.get(&day)?;
+                let ts = if part == 1 {
+                    stars.part_1
+                } else {
+                    stars.part_2?
+                };
+                Some((m, ts.saturating_sub(self.unlock_ts(day))))
+            })
+            .min_by_key(|(m, elapsed)| (*elapsed, m.id))
+    }
+}
+
+pub fn read(path: &str) -> Result<Leaderboard, Error> {
+    let content = fs::read_to_string(path)?;
+    Leaderboard::try_from(content.as_str())
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Days since the unix epoch for a date of the proleptic gregorian calendar.
+/// See: http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Inverse of [`days_from_civil`], returns `(year, month, day)`.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Formats a unix timestamp as `YYYY-MM-DD HH:MM:SS` (UTC).
+#[must_use]
+pub fn format_timestamp(ts: u64) -> String {
+    let (year, month, day) = civil_from_days(ts / 86400);
+    let secs = ts % 86400;
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        secs / 3600,
+        (secs / 60) % 60,
+        secs % 60
+    )
+}
+
+/// Formats a duration in seconds as `HH:MM:SS`, prefixed with days if needed.
+#[must_use]
+pub fn format_elapsed(secs: u64) -> String {
+    let days = secs / 86400;
+    let hms = format!(
+        "{:02}:{:02}:{:02}",
+        (secs / 3600) % 24,
+        (secs / 60) % 60,
+        secs % 60
+    );
+    if days > 0 {
+        format!("{days}d {hms}")
+    } else {
+        hms
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{
+        days_from_civil, format_elapsed, format_timestamp, parse_json_value, Json, Leaderboard,
+    };
+    use crate::day;
+
+    const LEADERBOARD: &str = r#"{
+        "owner_id": 1,
+        "event": "2023",
+        "members": {
+            "1": {
+                "id": 1, "name": "alice", "stars": 3, "local_score": 7, "global_score": 0,
+                "last_star_ts": 1701425000,
+                "completion_day_level": {
+                    "1": {
+                        "1": { "get_star_ts": 1701407100, "star_index": 0 },
+                        "2": { "get_star_ts": 1701408000, "star_index": 2 }
+                    },
+                    "2": { "1": { "get_star_ts": 1701493800, "star_index": 5 } }
+                }
+            },
+            "2": {
+                "id": 2, "name": null, "stars": 2, "local_score": 5, "global_score": 0,
+                "last_star_ts": 1701407700,
+                "completion_day_level": {
+                    "1": {
+                        "1": { "get_star_ts": 1701407000, "star_index": 1 },
+                        "2": { "get_star_ts": 1701407700, "star_index": 3 }
+                    }
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn parses_json_escapes() {
+        let (left, json) = parse_json_value(r#" ["a\"bé", -1.5e1, true, {}] "#).unwrap();
+        assert_eq!(left, "");
+        assert_eq!(
+            json,
+            Json::Array(vec![
+                Json::String("a\"bé".into()),
+                Json::Number(-15.0),
+                Json::Bool(true),
+                Json::Object(Default::default()),
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_leaderboard() {
+        let leaderboard = Leaderboard::try_from(LEADERBOARD).unwrap();
+        assert_eq!(leaderboard.event, 2023);
+        assert_eq!(leaderboard.members.len(), 2);
+        assert_eq!(leaderboard.members[0].display_name(), "alice");
+        assert_eq!(leaderboard.members[1].display_name(), "(anonymous user #2)");
+        let stars = leaderboard.members[0].days[&day!(1)];
+        assert_eq!(stars.delta(), Some(900));
+        assert_eq!(leaderboard.members[0].days[&day!(2)].part_2, None);
+    }
+
+    #[test]
+    fn finds_fastest_solvers() {
+        let leaderboard = Leaderboard::try_from(LEADERBOARD).unwrap();
+        let (member, elapsed) = leaderboard.fastest(day!(1), 1).unwrap();
+        assert_eq!(member.id, 2);
+        assert_eq!(elapsed, 1701407000 - 1701406800);
+        assert!(leaderboard.fastest(day!(2), 2).is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_leaderboard() {
+        assert!(Leaderboard::try_from("{\"event\": \"2023\"}").is_err());
+        assert!(Leaderboard::try_from("{\"event\": ").is_err());
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(format_timestamp(1701406800), "2023-12-01 05:00:00");
+        assert_eq!(format_elapsed(3725), "01:02:05");
+        assert_eq!(format_elapsed(90061), "1d 01:01:01");
+    }
+}
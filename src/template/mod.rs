@@ -1,8 +1,10 @@
 use crate::Day;
+use std::path::Path;
 use std::{env, fs};
 
 pub mod aoc_cli;
 pub mod commands;
+pub mod leaderboard;
 pub mod readme_benchmarks;
 pub mod runner;
 
@@ -10,16 +12,90 @@ pub const ANSI_ITALIC: &str = "\x1b[3m";
 pub const ANSI_BOLD: &str = "\x1b[1m";
 pub const ANSI_RESET: &str = "\x1b[0m";
 
+/// Options controlling how [`normalize_input_with`] cleans up puzzle inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Normalization {
+    /// Remove trailing spaces and tabs at the end of every line.
+    pub trim_trailing_whitespace: bool,
+    /// Remove blank lines at the end of the input, keeping a single final newline.
+    pub trim_trailing_blank_lines: bool,
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Self {
+            trim_trailing_whitespace: true,
+            trim_trailing_blank_lines: true,
+        }
+    }
+}
+
+/// Normalizes an input with the default [`Normalization`] options.
+#[must_use]
+pub fn normalize_input(input: &str) -> String {
+    normalize_input_with(input, Normalization::default())
+}
+
+/// Strips a leading byte order mark and converts CRLF line endings to LF, then applies `options`.
+/// Non-empty inputs always end with exactly one `\n` when trailing blank lines are trimmed.
+#[must_use]
+pub fn normalize_input_with(input: &str, options: Normalization) -> String {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut normalized = String::with_capacity(input.len());
+
+    for line in input.split_inclusive('\n') {
+        let has_newline = line.ends_with('\n');
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let line = if options.trim_trailing_whitespace {
+            line.trim_end_matches([' ', '\t'])
+        } else {
+            line
+        };
+        normalized.push_str(line);
+        if has_newline {
+            normalized.push('\n');
+        }
+    }
+
+    if options.trim_trailing_blank_lines {
+        let trimmed_len = normalized.trim_end_matches('\n').len();
+        normalized.truncate(trimmed_len);
+        if !normalized.is_empty() {
+            normalized.push('\n');
+        }
+    }
+
+    normalized
+}
+
+/// Reads a file to a string and normalizes it.
+/// Setting the `AOC_TEST_CRLF` environment variable converts the file to CRLF line endings
+/// before normalizing, so the example tests can be run as if the files were checked out on Windows.
+fn read_normalized(filepath: &Path) -> String {
+    let f = fs::read_to_string(filepath).expect("could not open input file");
+    if env::var_os("AOC_TEST_CRLF").is_some() {
+        normalize_input(&to_crlf(&f))
+    } else {
+        normalize_input(&f)
+    }
+}
+
+fn to_crlf(input: &str) -> String {
+    input.replace("\r\n", "\n").replace('\n', "\r\n")
+}
+
 /// Helper function that reads a text file to a string.
+/// The content is normalized with [`normalize_input`].
 #[must_use]
 pub fn read_file(folder: &str, day: Day) -> String {
     let cwd = env::current_dir().unwrap();
     let filepath = cwd.join("data").join(folder).join(format!("{day}.txt"));
-    let f = fs::read_to_string(filepath);
-    f.expect("could not open input file")
+    read_normalized(&filepath)
 }
 
 /// Helper function that reads a text file to string, appending a part suffix. E.g. like `01-2.txt`.
+/// The content is normalized with [`normalize_input`].
 #[must_use]
 pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
     let cwd = env::current_dir().unwrap();
@@ -27,22 +103,121 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
         .join("data")
         .join(folder)
         .join(format!("{day}-{part}.txt"));
-    let f = fs::read_to_string(filepath);
-    f.expect("could not open input file")
+    read_normalized(&filepath)
+}
+
+/// The command line arguments of a solution binary, read once when it starts and passed to
+/// [`read_input`] and to the day's `extras`, see [`solution!`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    args: Vec<String>,
+}
+
+impl Options {
+    /// The arguments the process was started with, the program name excepted.
+    #[must_use]
+    pub fn from_args() -> Self {
+        Self::new(env::args().skip(1))
+    }
+
+    pub fn new<S: Into<String>>(args: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            args: args.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Whether `--name` was passed.
+    #[must_use]
+    pub fn flag(&self, name: &str) -> bool {
+        self.args.iter().any(|arg| arg == name)
+    }
+
+    /// The argument following the first `--name`, if any.
+    #[must_use]
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.args
+            .iter()
+            .skip_while(|arg| *arg != name)
+            .nth(1)
+            .map(String::as_str)
+    }
+}
+
+/// Reads the puzzle input of a solution binary: `data/inputs/{day}.txt`, or the file passed with `--input <path>`.
+/// The content is normalized with [`normalize_input`].
+#[must_use]
+pub fn read_input(day: Day, options: &Options) -> String {
+    match options.value("--input") {
+        Some(path) => read_normalized(Path::new(path)),
+        None => read_file("inputs", day),
+    }
 }
 
 /// Creates the constant `DAY` and sets up the input and runner for each part.
+///
+/// The parts only see the input. Days with outputs of their own besides the answers, like
+/// images or reports, pass a `fn(&str, &Options)` as second argument: it runs once after both
+/// parts, with the command line [`Options`].
 #[macro_export]
 macro_rules! solution {
     ($day:expr) => {
+        advent_of_code::solution!($day, |_, _| {});
+    };
+    ($day:expr, $extras:expr) => {
         /// The current day.
         const DAY: advent_of_code::Day = advent_of_code::day!($day);
 
         fn main() {
             use advent_of_code::template::runner::*;
-            let input = advent_of_code::template::read_file("inputs", DAY);
+            let options = advent_of_code::template::Options::from_args();
+            let input = advent_of_code::template::read_input(DAY, &options);
             run_part(part_one, &input, DAY, 1);
             run_part(part_two, &input, DAY, 2);
+            let extras: fn(&str, &advent_of_code::template::Options) = $extras;
+            extras(&input, &options);
         }
     };
 }
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{normalize_input, normalize_input_with, to_crlf, Normalization, Options};
+
+    #[test]
+    fn converts_crlf() {
+        assert_eq!(normalize_input("a b\r\nc\r\n"), "a b\nc\n");
+        assert_eq!(normalize_input(&to_crlf("a\n\nb\n")), "a\n\nb\n");
+    }
+
+    #[test]
+    fn strips_bom() {
+        assert_eq!(normalize_input("\u{feff}Time: 7\n"), "Time: 7\n");
+    }
+
+    #[test]
+    fn trims_trailing_whitespace_and_blank_lines() {
+        assert_eq!(normalize_input("a \t\nb\n\n \n"), "a\nb\n");
+        assert_eq!(normalize_input("a"), "a\n");
+        assert_eq!(normalize_input("\n\n"), "");
+    }
+
+    #[test]
+    fn keeps_content_when_disabled() {
+        let options = Normalization {
+            trim_trailing_whitespace: false,
+            trim_trailing_blank_lines: false,
+        };
+        assert_eq!(normalize_input_with("a \r\n\r\n", options), "a \n\n");
+    }
+
+    #[test]
+    fn reads_options() {
+        let options = Options::new(["--render", "a.png", "--time", "--render", "b.png"]);
+        assert!(options.flag("--time"));
+        assert!(!options.flag("--render-all"));
+        assert_eq!(options.value("--render"), Some("a.png"));
+        assert_eq!(options.value("--time"), Some("--render"));
+        assert_eq!(options.value("--scale"), None);
+        assert_eq!(Options::default().value("--input"), None);
+    }
+}